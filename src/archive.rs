@@ -0,0 +1,118 @@
+use crate::{quarantine, ErrorBox};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::SystemTime,
+};
+
+/// Where archives go when `archive_dir` is not set,
+/// `<data dir>/eradicate/archives`.
+pub fn dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("eradicate").join("archives"))
+}
+
+/// The name of an archive made at `time`.
+pub fn file_name(time: SystemTime) -> String {
+    // colons are not allowed in file names everywhere
+    let time = humantime::format_rfc3339_seconds(time)
+        .to_string()
+        .replace(':', "-");
+    format!("eradicated-{}.tar.gz", time)
+}
+
+/// Writes an archive on a worker thread, then checks it.
+pub struct Archiver {
+    /// Where the archive goes.
+    pub path: PathBuf,
+    /// How many entries go in.
+    pub count: usize,
+    /// The entries going in, what gets deleted once the archive checks out.
+    pub paths: Vec<PathBuf>,
+    receiver: Receiver<Result<(), String>>,
+}
+
+impl Archiver {
+    pub fn start(paths: Vec<PathBuf>, root: PathBuf, dest: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let count = paths.len();
+        let path = dest.clone();
+        let archived = paths.clone();
+        thread::spawn(move || {
+            let result = write(&paths, &root, &dest).and_then(|()| verify(&dest, &paths, &root));
+            // a broken archive is no safety copy
+            if result.is_err() {
+                let _ = fs::remove_file(&dest);
+            }
+            let _ = sender.send(result.map_err(|err| err.to_string()));
+        });
+        Archiver {
+            path,
+            count,
+            paths: archived,
+            receiver,
+        }
+    }
+
+    /// Whether the archive was written and checked, once it's done, without
+    /// blocking.
+    pub fn poll(&self) -> Option<Result<(), String>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(String::from("the archiver went away"))),
+        }
+    }
+
+    pub fn wait(&self) -> Result<(), String> {
+        self.receiver
+            .recv()
+            .unwrap_or_else(|_| Err(String::from("the archiver went away")))
+    }
+}
+
+/// Writes `paths` into a new gzipped tarball at `dest`, each at its path
+/// relative to `root` like in the quarantine. Symlinks are stored as links.
+pub fn write(paths: &[PathBuf], root: &Path, dest: &Path) -> Result<(), ErrorBox> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = File::options().write(true).create_new(true).open(dest)?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    builder.follow_symlinks(false);
+    for path in paths {
+        let name = quarantine::stored_path(path, root);
+        match path.symlink_metadata()?.is_dir() {
+            true => builder.append_dir_all(&name, path)?,
+            false => builder.append_path_with_name(path, &name)?,
+        }
+    }
+    builder.into_inner()?.finish()?.sync_all()?;
+    Ok(())
+}
+
+/// Reads the archive at `dest` to the end, checking every entry decompresses
+/// and that each of `paths` is in there.
+pub fn verify(dest: &Path, paths: &[PathBuf], root: &Path) -> Result<(), ErrorBox> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(dest)?));
+    let mut names = HashSet::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        io::copy(&mut entry, &mut io::sink())?;
+        names.insert(entry.path()?.into_owned());
+    }
+    // the checksum of the gzip stream is only checked at its very end
+    io::copy(&mut archive.into_inner(), &mut io::sink())?;
+
+    match paths
+        .iter()
+        .find(|path| !names.contains(&quarantine::stored_path(path, root)))
+    {
+        Some(path) => Err(format!("{} is missing from the archive", path.display()).into()),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,62 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Eradicate with patterns and no mercy
+#[derive(Parser)]
+#[clap(version, about)]
+pub struct Args {
+    /// Directory to search in, the current one by default
+    #[clap(value_name = "ROOT")]
+    pub root: Option<PathBuf>,
+
+    /// Glob pattern to search for on startup
+    #[clap(short, long, group = "search")]
+    pub pattern: Option<String>,
+
+    /// Search with the preset called NAME on startup, from the config or built in
+    #[clap(long, value_name = "NAME", group = "search")]
+    pub profile: Option<String>,
+
+    /// List the paths in FILE, one a line, instead of searching
+    #[clap(long, value_name = "FILE", group = "search")]
+    pub import: Option<PathBuf>,
+
+    /// List the paths piped in, one a line, instead of searching, like
+    /// `fd -t d node_modules | eradicate-tui --stdin`
+    #[clap(long, group = "search")]
+    pub stdin: bool,
+
+    /// Write the matches to FILE, as JSON when it ends with .json, or to
+    /// stdout with -, without launching the interface
+    #[clap(long, value_name = "FILE", requires = "search", conflicts_with = "yes")]
+    pub export: Option<PathBuf>,
+
+    /// Delete every match without launching the interface
+    #[clap(short, long, requires = "search")]
+    pub yes: bool,
+
+    /// Delete for good instead of moving to the trash
+    #[clap(long)]
+    pub permanent: bool,
+
+    /// Move to the quarantine instead of the trash, to restore or purge later
+    #[clap(long, conflicts_with = "permanent")]
+    pub quarantine: bool,
+
+    /// Don't write deletions to the audit log
+    #[clap(long)]
+    pub no_log: bool,
+
+    /// Report what would be deleted instead of deleting it
+    #[clap(long)]
+    pub dry_run: bool,
+
+    /// Print the dry run report as JSON, with the type, size and modification
+    /// time of each path, without launching the interface
+    #[clap(long, requires_all = &["dry-run", "search"], conflicts_with = "export")]
+    pub json: bool,
+
+    /// Also write the dry run report to this file
+    #[clap(long, value_name = "FILE", requires = "dry-run")]
+    pub report: Option<PathBuf>,
+}
@@ -0,0 +1,156 @@
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+};
+
+/// Paths passed to one run of the command, like xargs, to stay below the
+/// limit on the length of a command line.
+const PATHS_PER_RUN: usize = 512;
+
+pub enum CommandMessage {
+    /// A line the command wrote to stdout or stderr.
+    Output(String),
+    /// Every run is done, with how many of them failed.
+    Done { failed: usize },
+}
+
+/// An external command run on paths on a worker thread.
+pub struct CommandRun {
+    pub command: String,
+    /// How many paths the command got.
+    pub count: usize,
+    receiver: Receiver<CommandMessage>,
+}
+
+impl CommandRun {
+    /// Runs `command` through the shell in `dir`, with the paths appended as
+    /// arguments, as many runs as it takes.
+    pub fn start(command: String, paths: Vec<PathBuf>, dir: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let count = paths.len();
+        let line = command.clone();
+        thread::spawn(move || {
+            let failed = paths
+                .chunks(PATHS_PER_RUN)
+                .filter(|chunk| !run(&line, chunk, &dir, &sender))
+                .count();
+            let _ = sender.send(CommandMessage::Done { failed });
+        });
+        CommandRun {
+            command,
+            count,
+            receiver,
+        }
+    }
+
+    /// Messages received so far, without blocking. A worker that went away
+    /// counts as done.
+    pub fn poll(&self) -> Vec<CommandMessage> {
+        let mut messages = vec![];
+        loop {
+            match self.receiver.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    messages.push(CommandMessage::Done { failed: 0 });
+                    break;
+                }
+            }
+        }
+        messages
+    }
+
+    /// Every remaining message, blocking until the command is done.
+    pub fn wait(&self) -> Vec<CommandMessage> {
+        let mut messages = vec![];
+        for message in self.receiver.iter() {
+            let done = matches!(message, CommandMessage::Done { .. });
+            messages.push(message);
+            if done {
+                return messages;
+            }
+        }
+        messages.push(CommandMessage::Done { failed: 0 });
+        messages
+    }
+}
+
+/// Runs the command once on `paths`, sending what it writes as it goes.
+/// Returns whether it succeeded.
+fn run(command: &str, paths: &[PathBuf], dir: &PathBuf, sender: &Sender<CommandMessage>) -> bool {
+    let child = shell(command)
+        .args(paths)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(err) => {
+            let _ = sender.send(CommandMessage::Output(format!(
+                "could not run {}: {}",
+                command, err
+            )));
+            return false;
+        }
+    };
+
+    let errors = child.stderr.take().map(|stderr| {
+        let sender = sender.clone();
+        thread::spawn(move || forward(stderr, &sender))
+    });
+    if let Some(stdout) = child.stdout.take() {
+        forward(stdout, sender);
+    }
+    if let Some(errors) = errors {
+        let _ = errors.join();
+    }
+
+    let outcome = match child.wait() {
+        Ok(status) if status.success() => return true,
+        Ok(status) => format!("{} exited with {}", command, status),
+        Err(err) => format!("could not wait for {}: {}", command, err),
+    };
+    let _ = sender.send(CommandMessage::Output(outcome));
+    false
+}
+
+/// Sends each line read from `output`, invalid UTF-8 replaced.
+fn forward(output: impl Read, sender: &Sender<CommandMessage>) {
+    let mut reader = BufReader::new(output);
+    let mut line = vec![];
+    while let Ok(n) = reader.read_until(b'\n', &mut line) {
+        if n == 0 {
+            return;
+        }
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end_matches(['\n', '\r']).to_string();
+        if sender.send(CommandMessage::Output(text)).is_err() {
+            return;
+        }
+        line.clear();
+    }
+}
+
+/// `command` run by the shell with the arguments added after it.
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    // "$@" puts the arguments after the command, each quoted
+    shell
+        .arg("-c")
+        .arg(format!("{} \"$@\"", command))
+        .arg("eradicate");
+    shell
+}
+
+#[cfg(not(unix))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
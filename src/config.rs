@@ -0,0 +1,323 @@
+use crate::{humanize, presets::Preset, theme::ThemeName, ErrorBox};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{convert::TryFrom, fs, path::PathBuf};
+
+const CONFIG_FILE: &str = "config.toml";
+/// The shortest `tick_rate`, a shorter one would keep the event thread busy.
+pub const MIN_TICK_RATE: u64 = 10;
+
+/// A piece of entry metadata that can be shown in the result list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    Type,
+    Size,
+    Mtime,
+    Permissions,
+    Path,
+}
+
+/// How many rows each entry takes in the result list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Density {
+    /// Metadata row, path row and a divider.
+    Comfortable,
+    /// Metadata row and path row.
+    Dense,
+    /// One row with the marker, a type glyph, the size and the path.
+    Compact,
+}
+
+/// When `q` asks before quitting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuitConfirm {
+    Always,
+    /// Only while some entries are marked for deletion.
+    WhenMarked,
+    Never,
+}
+
+/// A byte count written as a human readable size, like `100M`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ByteSize(pub u64);
+
+impl TryFrom<String> for ByteSize {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        humanize::parse_size(&value)
+            .map(ByteSize)
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl From<ByteSize> for String {
+    /// Uses the biggest unit that keeps the value exact, so it reads back the same.
+    fn from(size: ByteSize) -> Self {
+        let units = ["", "K", "M", "G", "T", "P"];
+        let mut value = size.0;
+        let mut unit = 0;
+        while value != 0 && value.is_multiple_of(1024) && unit < units.len() - 1 {
+            value /= 1024;
+            unit += 1;
+        }
+        format!("{}{}", value, units[unit])
+    }
+}
+
+/// Entries at least `min_size` big are drawn in `color`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeTier {
+    pub min_size: ByteSize,
+    /// A color name like `red` or `lightyellow`, or `#rrggbb`.
+    pub color: String,
+}
+
+impl SizeTier {
+    pub fn new(min_size: u64, color: &str) -> Self {
+        SizeTier {
+            min_size: ByteSize(min_size),
+            color: color.to_string(),
+        }
+    }
+}
+
+/// Background colors of the mode badge, see `SizeTier::color` for the format.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModeColors {
+    pub normal: String,
+    pub insert: String,
+    pub prompt: String,
+    pub presets: String,
+    pub confirm: String,
+    pub quarantine: String,
+    pub command: String,
+    pub visual: String,
+}
+
+impl Default for ModeColors {
+    fn default() -> Self {
+        ModeColors {
+            normal: String::from("blue"),
+            insert: String::from("yellow"),
+            prompt: String::from("magenta"),
+            presets: String::from("cyan"),
+            confirm: String::from("red"),
+            quarantine: String::from("green"),
+            command: String::from("white"),
+            visual: String::from("lightmagenta"),
+        }
+    }
+}
+
+/// User settings, read from `<config dir>/eradicate-tui/config.toml`.
+/// Missing fields fall back to their defaults.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Columns rendered for each entry, in order. The path always gets its own row.
+    pub columns: Vec<Column>,
+    pub density: Density,
+    /// Draw the result list compact above this many results, until a density
+    /// is picked with `z`. 0 keeps `density` always.
+    pub compact_above: usize,
+    /// Overridden by `no_color` when the `NO_COLOR` variable is set.
+    pub theme: ThemeName,
+    /// Plain borders and ASCII-only markers, for terminals without good unicode support.
+    pub ascii_mode: bool,
+    /// Width of the left pane, in percent of the terminal.
+    pub split_ratio: u16,
+    pub show_help: bool,
+    pub show_histogram: bool,
+    /// Preview the selected file or directory below the pattern.
+    pub show_preview: bool,
+    /// Insert each search result at its sorted position instead of sorting
+    /// once the search is done.
+    pub keep_sorted: bool,
+    /// Ask before deleting each marked directory, files are deleted right away.
+    pub per_dir_confirm: bool,
+    pub confirm_on_quit: QuitConfirm,
+    /// Delete for good instead of moving to the trash, `X` always does.
+    pub permanent_delete: bool,
+    /// Move deleted entries to `<data dir>/eradicate/quarantine` instead of
+    /// the trash, to restore or purge them later from the quarantine view.
+    pub quarantine: bool,
+    /// Where archive then delete writes its tarballs,
+    /// `<data dir>/eradicate/archives` when not set.
+    pub archive_dir: Option<PathBuf>,
+    /// Threads deleting marked entries, 0 uses one per CPU. Only used
+    /// without `per_dir_confirm`.
+    pub delete_threads: usize,
+    /// Threads walking directories during a search, 0 uses one per CPU.
+    /// With 1, results come in file name order.
+    pub search_threads: usize,
+    /// Matches listed at once, the rest wait for `load_more_results`.
+    /// 0 lists every match.
+    pub max_results: usize,
+    /// Append a JSON line per deleted or failed entry, with the time, size and
+    /// outcome, to `<config dir>/eradicate-tui/deletions.jsonl`.
+    pub log_deletions: bool,
+    /// The deletion log is rotated once it grows past this size.
+    pub log_max_size: ByteSize,
+    /// Re-run the pattern while it's typed in insert mode.
+    pub live_search: bool,
+    /// Whether patterns match case sensitively until toggled.
+    pub case_sensitive: bool,
+    /// Whether search results start out marked for deletion.
+    pub mark_new_entries: bool,
+    /// Milliseconds between two redraws while no key is pressed, raised to
+    /// `MIN_TICK_RATE` when lower.
+    #[serde(deserialize_with = "tick_rate")]
+    pub tick_rate: u64,
+    /// Skip whatever `.gitignore` or `.eradicateignore` files list.
+    pub respect_ignore: bool,
+    /// Re-run the pattern when something changes below the search root.
+    pub watch: bool,
+    /// Re-run the pattern once a deletion is done, listing what matches now.
+    pub refresh_after_delete: bool,
+    /// Paths that can't be marked or deleted, on top of the filesystem root,
+    /// the home directory, git internals and mount points.
+    pub protected_paths: Vec<PathBuf>,
+    /// Colors for big entries, the highest matching tier wins.
+    pub size_tiers: Vec<SizeTier>,
+    /// Extra quick clean presets, listed after the built-in ones.
+    pub presets: Vec<Preset>,
+    pub keymap: Keymap,
+    pub mode_colors: ModeColors,
+}
+
+/// Key names are single characters (`y`) or special keys (`Enter`, `Esc`).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keymap {
+    /// Keys accepting a confirmation dialog.
+    pub confirm: Vec<String>,
+    /// Keys dismissing a confirmation dialog.
+    pub cancel: Vec<String>,
+    /// Keys moving the selection up in the result list.
+    pub up: Vec<String>,
+    /// Keys moving the selection down in the result list.
+    pub down: Vec<String>,
+    /// Keys marking or unmarking the selected entry.
+    pub toggle: Vec<String>,
+    /// Keys asking to delete the marked entries.
+    pub delete: Vec<String>,
+    /// Keys switching to insert mode to edit the pattern.
+    pub insert: Vec<String>,
+    pub quit: Vec<String>,
+}
+
+fn keys(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap {
+            confirm: keys(&["y"]),
+            cancel: keys(&["n", "Esc"]),
+            up: keys(&["k", "Up"]),
+            down: keys(&["j", "Down"]),
+            toggle: keys(&["Enter"]),
+            delete: keys(&["d"]),
+            insert: keys(&["i"]),
+            quit: keys(&["q"]),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            columns: vec![Column::Type, Column::Size, Column::Mtime, Column::Path],
+            density: Density::Comfortable,
+            compact_above: 100,
+            theme: ThemeName::Dark,
+            ascii_mode: false,
+            split_ratio: 50,
+            show_help: true,
+            show_histogram: false,
+            show_preview: false,
+            keep_sorted: false,
+            per_dir_confirm: false,
+            confirm_on_quit: QuitConfirm::WhenMarked,
+            permanent_delete: false,
+            quarantine: false,
+            archive_dir: None,
+            delete_threads: 1,
+            search_threads: 0,
+            max_results: 10_000,
+            log_deletions: true,
+            log_max_size: ByteSize(1024 * 1024),
+            live_search: true,
+            case_sensitive: true,
+            mark_new_entries: true,
+            tick_rate: 250,
+            respect_ignore: false,
+            watch: false,
+            refresh_after_delete: true,
+            protected_paths: vec![],
+            size_tiers: vec![
+                SizeTier::new(1024 * 1024 * 1024, "red"),
+                SizeTier::new(100 * 1024 * 1024, "yellow"),
+            ],
+            presets: vec![],
+            keymap: Keymap::default(),
+            mode_colors: ModeColors::default(),
+        }
+    }
+}
+
+fn tick_rate<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    Ok(u64::deserialize(deserializer)?.max(MIN_TICK_RATE))
+}
+
+impl Config {
+    pub fn dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("eradicate-tui"))
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join(CONFIG_FILE))
+    }
+
+    /// Loads the config file, or the defaults when there is none.
+    pub fn load() -> Result<Self, ErrorBox> {
+        let path = match Config::path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(Config::default()),
+        };
+
+        let content = fs::read_to_string(&path)?;
+        let config = toml::from_str(&content)
+            .map_err(|e| format!("invalid config {}: {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    /// The color name of the highest size tier reached by `size`.
+    pub fn size_color(&self, size: u64) -> Option<&str> {
+        self.size_tiers
+            .iter()
+            .filter(|tier| size >= tier.min_size.0)
+            .max_by_key(|tier| tier.min_size)
+            .map(|tier| tier.color.as_str())
+    }
+
+    /// Writes the config file, creating its directory when needed.
+    pub fn save(&self) -> Result<(), ErrorBox> {
+        let dir = Config::dir().ok_or("no config directory on this platform")?;
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(CONFIG_FILE), self.to_toml()?)?;
+        Ok(())
+    }
+
+    pub fn to_toml(&self) -> Result<String, ErrorBox> {
+        // going through a Value puts plain values before tables, as toml requires
+        let value = toml::Value::try_from(self)?;
+        Ok(toml::to_string_pretty(&value)?)
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{quarantine, PathEntry};
+use rayon::{prelude::*, ThreadPoolBuilder};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc,
+    },
+    thread,
+};
+
+pub enum DeleteMessage {
+    /// The entry is being deleted now.
+    Removing(PathBuf),
+    /// The entry is gone, with the bytes it took.
+    Removed(PathBuf, u64),
+    Failed(PathBuf, String),
+    Done,
+}
+
+/// Where deleted entries go.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Disposal {
+    Trash,
+    /// Moved into the quarantine batch directory `batch`, at their path
+    /// relative to `root`.
+    Quarantine {
+        batch: PathBuf,
+        root: PathBuf,
+    },
+    /// Deleted for good.
+    Permanent,
+}
+
+/// Entries being deleted on a worker thread.
+pub struct Deletion {
+    receiver: Receiver<DeleteMessage>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Deletion {
+    /// Deletes `entries` in order on one thread, or on `threads` threads when
+    /// it's not 1, deepest paths first so a directory only goes once its
+    /// children are gone. 0 threads uses one per CPU.
+    pub fn start(entries: Vec<PathEntry>, disposal: Disposal, threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancelled);
+
+        thread::spawn(move || {
+            if threads == 1 {
+                for entry in entries {
+                    if flag.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    remove(entry, &disposal, &sender);
+                }
+            } else {
+                remove_in_parallel(entries, &disposal, threads, &flag, &sender);
+            }
+            let _ = sender.send(DeleteMessage::Done);
+        });
+
+        Deletion {
+            receiver,
+            cancelled,
+        }
+    }
+
+    /// Asks the worker to stop before the next entry.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Every remaining message, blocking until the worker is done.
+    pub fn wait(&self) -> Vec<DeleteMessage> {
+        let mut messages = vec![];
+        for message in self.receiver.iter() {
+            let done = matches!(message, DeleteMessage::Done);
+            messages.push(message);
+            if done {
+                return messages;
+            }
+        }
+        messages.push(DeleteMessage::Done);
+        messages
+    }
+
+    /// Messages received so far, without blocking. A worker that went away
+    /// counts as done.
+    pub fn poll(&self) -> Vec<DeleteMessage> {
+        let mut messages = vec![];
+        loop {
+            match self.receiver.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    messages.push(DeleteMessage::Done);
+                    break;
+                }
+            }
+        }
+        messages
+    }
+}
+
+fn remove(entry: PathEntry, disposal: &Disposal, sender: &Sender<DeleteMessage>) {
+    let _ = sender.send(DeleteMessage::Removing(entry.pathbuf.clone()));
+    let size = entry.disk_size();
+    let result = match disposal {
+        Disposal::Trash => entry.remove(false),
+        Disposal::Quarantine { batch, root } => quarantine::stash(&entry.pathbuf, batch, root),
+        Disposal::Permanent => entry.remove(true),
+    };
+    let message = match result {
+        Ok(()) => DeleteMessage::Removed(entry.pathbuf, size),
+        Err(err) => DeleteMessage::Failed(entry.pathbuf, err.to_string()),
+    };
+    let _ = sender.send(message);
+}
+
+fn remove_in_parallel(
+    entries: Vec<PathEntry>,
+    disposal: &Disposal,
+    threads: usize,
+    cancelled: &AtomicBool,
+    sender: &Sender<DeleteMessage>,
+) {
+    let pool = match ThreadPoolBuilder::new().num_threads(threads).build() {
+        Ok(pool) => pool,
+        Err(err) => {
+            for entry in entries {
+                let _ = sender.send(DeleteMessage::Failed(entry.pathbuf, err.to_string()));
+            }
+            return;
+        }
+    };
+
+    let mut levels: BTreeMap<usize, Vec<PathEntry>> = BTreeMap::new();
+    for entry in entries {
+        let depth = entry.pathbuf.components().count();
+        levels.entry(depth).or_default().push(entry);
+    }
+
+    for entries in levels.into_values().rev() {
+        pool.install(|| {
+            entries.into_par_iter().for_each(|entry| {
+                if !cancelled.load(Ordering::Relaxed) {
+                    remove(entry, disposal, sender);
+                }
+            })
+        });
+    }
+}
@@ -0,0 +1,141 @@
+use rayon::prelude::*;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs::{self, File},
+    hash::Hasher,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+const CHUNK_LEN: usize = 64 * 1024;
+
+/// Looks for duplicate files on a worker thread.
+pub struct DuplicateFinder {
+    receiver: Receiver<Vec<Vec<PathBuf>>>,
+}
+
+impl DuplicateFinder {
+    pub fn start(paths: Vec<PathBuf>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(find(paths));
+        });
+        DuplicateFinder { receiver }
+    }
+
+    /// The groups once they're all found, without blocking. A worker that
+    /// went away found none.
+    pub fn poll(&self) -> Option<Vec<Vec<PathBuf>>> {
+        match self.receiver.try_recv() {
+            Ok(groups) => Some(groups),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(vec![]),
+        }
+    }
+
+    pub fn wait(&self) -> Vec<Vec<PathBuf>> {
+        self.receiver.recv().unwrap_or_default()
+    }
+}
+
+/// Groups of files among `paths` with the same content, each sorted by
+/// path. Only files of the same size get hashed, and files with the same
+/// hash are compared byte by byte before being called duplicates. Empty and
+/// unreadable files are left out.
+pub fn find(paths: Vec<PathBuf>) -> Vec<Vec<PathBuf>> {
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for path in paths {
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.is_file() && metadata.len() > 0 => {
+                by_size.entry(metadata.len()).or_default().push(path);
+            }
+            _ => {}
+        }
+    }
+
+    let candidates: Vec<PathBuf> = by_size
+        .into_values()
+        .filter(|same_size| same_size.len() > 1)
+        .flatten()
+        .collect();
+    let hashes: Vec<(PathBuf, u64)> = candidates
+        .into_par_iter()
+        .filter_map(|path| hash_file(&path).ok().map(|hash| (path, hash)))
+        .collect();
+
+    let mut by_hash: HashMap<(u64, u64), Vec<PathBuf>> = HashMap::new();
+    for (path, hash) in hashes {
+        let len = fs::metadata(&path).map_or(0, |m| m.len());
+        by_hash.entry((len, hash)).or_default().push(path);
+    }
+
+    let mut groups: Vec<Vec<PathBuf>> = by_hash
+        .into_values()
+        .filter(|same_hash| same_hash.len() > 1)
+        .flat_map(split_by_content)
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort();
+            group
+        })
+        .collect();
+    groups.sort();
+    groups
+}
+
+/// Splits files with the same hash into groups with the very same bytes,
+/// a hash collision must not get a file deleted.
+fn split_by_content(paths: Vec<PathBuf>) -> Vec<Vec<PathBuf>> {
+    let mut groups: Vec<Vec<PathBuf>> = vec![];
+    for path in paths {
+        let group = groups
+            .iter_mut()
+            .find(|group| same_content(&group[0], &path).unwrap_or(false));
+        match group {
+            Some(group) => group.push(path),
+            None => groups.push(vec![path]),
+        }
+    }
+    groups
+}
+
+fn hash_file(path: &Path) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut hasher = DefaultHasher::new();
+    let mut buf = vec![0; CHUNK_LEN];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            return Ok(hasher.finish());
+        }
+        hasher.write(&buf[..n]);
+    }
+}
+
+fn same_content(a: &Path, b: &Path) -> io::Result<bool> {
+    let (mut a, mut b) = (File::open(a)?, File::open(b)?);
+    let (mut buf_a, mut buf_b) = (vec![0; CHUNK_LEN], vec![0; CHUNK_LEN]);
+    loop {
+        let n = read_full(&mut a, &mut buf_a)?;
+        if n != read_full(&mut b, &mut buf_b)? || buf_a[..n] != buf_b[..n] {
+            return Ok(false);
+        }
+        if n == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+/// Fills `buf` unless the file ends first, returns how much was read.
+fn read_full(file: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
@@ -0,0 +1,110 @@
+use crate::ErrorBox;
+use crossterm::event::{self, Event};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// How often the input thread checks whether it should stop.
+const INPUT_POLL: Duration = Duration::from_millis(100);
+
+/// Everything the event loop reacts to.
+#[derive(Debug)]
+pub enum AppEvent {
+    /// A key, mouse or resize event from the terminal.
+    Input(Event),
+    /// Time to pick up what the workers sent and advance the spinner.
+    Tick,
+    /// SIGINT or SIGTERM arrived, stop the work in progress and quit.
+    Interrupt,
+}
+
+/// A flag raised by SIGINT and SIGTERM instead of getting killed, so the
+/// work in progress can be stopped cleanly. A second signal while the flag
+/// is up still exits right away.
+pub fn interrupt_flag() -> Result<Arc<AtomicBool>, ErrorBox> {
+    let flag = Arc::new(AtomicBool::new(false));
+    for signal in [SIGINT, SIGTERM] {
+        signal_hook::flag::register_conditional_shutdown(signal, 1, Arc::clone(&flag))?;
+        signal_hook::flag::register(signal, Arc::clone(&flag))?;
+    }
+    Ok(flag)
+}
+
+/// Terminal input read on one thread, ticks and signals sent from another,
+/// funneled into one channel. Both threads stop once this is dropped, and
+/// terminal input is left alone from then on.
+pub struct Events {
+    receiver: Receiver<AppEvent>,
+    stopped: Arc<AtomicBool>,
+    input: Option<JoinHandle<()>>,
+}
+
+impl Events {
+    pub fn new(tick_rate: Duration, interrupted: Arc<AtomicBool>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let keys = sender.clone();
+        let flag = Arc::clone(&stopped);
+        let input = thread::spawn(move || {
+            // polling instead of a blocking read lets the thread see the flag
+            while !flag.load(Ordering::Relaxed) {
+                match event::poll(INPUT_POLL) {
+                    Ok(false) => continue,
+                    Ok(true) => {}
+                    Err(_) => return,
+                }
+                let event = match event::read() {
+                    Ok(event) => event,
+                    Err(_) => return,
+                };
+                if keys.send(AppEvent::Input(event)).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let flag = Arc::clone(&stopped);
+        thread::spawn(move || {
+            while !flag.load(Ordering::Relaxed) {
+                thread::sleep(tick_rate);
+                // the flag stays up, so a second signal can still kill
+                let event = match interrupted.load(Ordering::Relaxed) {
+                    true => AppEvent::Interrupt,
+                    false => AppEvent::Tick,
+                };
+                if sender.send(event).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Events {
+            receiver,
+            stopped,
+            input: Some(input),
+        }
+    }
+
+    /// The next event, blocking until there is one.
+    pub fn recv(&self) -> Result<AppEvent, ErrorBox> {
+        Ok(self.receiver.recv()?)
+    }
+}
+
+impl Drop for Events {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        // a program run next must get the keys, not this thread
+        if let Some(input) = self.input.take() {
+            let _ = input.join();
+        }
+    }
+}
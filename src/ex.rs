@@ -0,0 +1,87 @@
+use crate::{ErrorBox, SortKey};
+use std::path::PathBuf;
+
+/// The commands of the `:` command line, with the shortest abbreviation
+/// each one takes.
+const NAMES: [(&str, usize); 6] = [
+    ("sort", 2),
+    ("filter", 1),
+    ("delete", 1),
+    ("cd", 2),
+    ("write", 1),
+    ("quit", 1),
+];
+
+/// A command typed on the `:` command line, like in vi.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExCommand {
+    /// `sort[!] name|size|mtime|type|none`, `!` sorting in descending order.
+    Sort {
+        key: Option<SortKey>,
+        descending: bool,
+    },
+    /// `filter [query]`, hiding the results that don't match, or showing
+    /// them all again without a query.
+    Filter(String),
+    /// `delete[!]`, `!` deleting the marked entries without asking first.
+    Delete { force: bool },
+    /// `cd [dir]`, searching in another root, the home directory by default.
+    Cd(Option<PathBuf>),
+    /// `write file`, exporting the results.
+    Write(PathBuf),
+    /// `quit[!]`, `!` quitting without asking first.
+    Quit { force: bool },
+}
+
+impl ExCommand {
+    /// Parses a command line, without the leading `:`. An empty line is no
+    /// command at all.
+    pub fn parse(line: &str) -> Result<Option<Self>, ErrorBox> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+        let (word, arg) = match line.split_once(char::is_whitespace) {
+            Some((word, arg)) => (word, arg.trim()),
+            None => (line, ""),
+        };
+        let (word, bang) = match word.strip_suffix('!') {
+            Some(word) => (word, true),
+            None => (word, false),
+        };
+
+        let name = NAMES
+            .iter()
+            .find(|(name, shortest)| word.len() >= *shortest && name.starts_with(word))
+            .map(|(name, _)| *name)
+            .ok_or_else(|| format!("not a command: {}", word))?;
+        if bang && !matches!(name, "sort" | "delete" | "quit") {
+            return Err(format!("{} takes no !", name).into());
+        }
+        if !arg.is_empty() && matches!(name, "delete" | "quit") {
+            return Err(format!("{} takes no argument", name).into());
+        }
+
+        let command = match name {
+            "sort" => {
+                let key = match arg {
+                    "none" => None,
+                    _ => Some(SortKey::from_name(arg).ok_or(
+                        "sort by name, size, mtime or type, or none for the search order",
+                    )?),
+                };
+                ExCommand::Sort {
+                    key,
+                    descending: bang,
+                }
+            }
+            "filter" => ExCommand::Filter(arg.to_string()),
+            "delete" => ExCommand::Delete { force: bang },
+            "cd" => ExCommand::Cd(Some(arg).filter(|arg| !arg.is_empty()).map(PathBuf::from)),
+            "write" if arg.is_empty() => return Err("write needs a file name".into()),
+            "write" => ExCommand::Write(PathBuf::from(arg)),
+            _ => ExCommand::Quit { force: bang },
+        };
+        Ok(Some(command))
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{ErrorBox, PathEntry};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+/// How exported entries are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// One path a line.
+    Plain,
+    /// An array with an object for each entry.
+    Json,
+}
+
+impl Format {
+    /// JSON for `.json` files, plain text otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().is_some_and(|ext| ext == "json") {
+            true => Format::Json,
+            false => Format::Plain,
+        }
+    }
+}
+
+/// An entry as it's exported.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ExportedEntry {
+    pub path: PathBuf,
+    /// `file`, `dir`, or the kind of a special file like `fifo`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Bytes of regular files, unknown while a directory is being measured.
+    pub size: Option<u64>,
+    /// RFC 3339 timestamp in UTC.
+    pub modified: Option<String>,
+    pub marked: bool,
+}
+
+impl ExportedEntry {
+    pub fn new(entry: &PathEntry, size: Option<u64>) -> Self {
+        let kind = match entry.special {
+            Some(kind) => kind.to_lowercase(),
+            None if entry.is_dir => String::from("dir"),
+            None => String::from("file"),
+        };
+        ExportedEntry {
+            path: entry.pathbuf.clone(),
+            kind,
+            size,
+            modified: entry
+                .modified
+                .map(|time| humantime::format_rfc3339_seconds(time).to_string()),
+            marked: entry.is_delete(),
+        }
+    }
+}
+
+/// What a dry run would remove, as `--json` prints it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DryRunDocument {
+    pub root: PathBuf,
+    pub pattern: String,
+    pub files: usize,
+    pub dirs: usize,
+    /// Bytes of regular files in every entry.
+    pub total_size: u64,
+    pub entries: Vec<ExportedEntry>,
+}
+
+impl DryRunDocument {
+    pub fn to_json(&self) -> Result<String, ErrorBox> {
+        let mut text = serde_json::to_string_pretty(self)?;
+        text.push('\n');
+        Ok(text)
+    }
+}
+
+/// The entries written in `format`, ending with a line break.
+pub fn render(entries: &[ExportedEntry], format: Format) -> Result<String, ErrorBox> {
+    let mut text = match format {
+        Format::Plain => entries
+            .iter()
+            .map(|entry| entry.path.display().to_string())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Format::Json => serde_json::to_string_pretty(entries)?,
+    };
+    text.push('\n');
+    Ok(text)
+}
+
+/// The paths of a newline separated list, like `fd` prints, without blank
+/// lines and repeats.
+pub fn parse_paths(text: &str) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    text.lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.trim().is_empty())
+        .map(PathBuf::from)
+        .filter(|path| seen.insert(path.clone()))
+        .collect()
+}
@@ -0,0 +1,174 @@
+use crate::{humanize, ErrorBox, PathEntry};
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+/// A span of modification times, parsed from `<start>..<end>`.
+///
+/// Each bound is either an absolute date (`2022-06-30`, `2022-06-30 12:00:00`)
+/// or a relative offset into the past (`30d`, `2h 30m`), and may be left empty
+/// to keep that side open. A leading `!` selects everything outside the range.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeRange {
+    pub start: Option<SystemTime>,
+    pub end: Option<SystemTime>,
+    pub outside: bool,
+}
+
+impl TimeRange {
+    pub fn parse(input: &str) -> Result<Self, ErrorBox> {
+        let input = input.trim();
+        let (outside, input) = match input.strip_prefix('!') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, input),
+        };
+
+        let (start, end) = input
+            .split_once("..")
+            .ok_or("expected a range like 30d..7d or 2022-01-01..2022-06-30")?;
+        let start = parse_bound(start)?;
+        let end = parse_bound(end)?;
+
+        if let (Some(start), Some(end)) = (start, end) {
+            if start > end {
+                return Err("the start of the range is after its end".into());
+            }
+        }
+
+        Ok(TimeRange {
+            start,
+            end,
+            outside,
+        })
+    }
+
+    pub fn contains(&self, time: SystemTime) -> bool {
+        let within =
+            self.start.is_none_or(|start| time >= start) && self.end.is_none_or(|end| time <= end);
+        within != self.outside
+    }
+}
+
+fn parse_bound(bound: &str) -> Result<Option<SystemTime>, ErrorBox> {
+    let bound = bound.trim();
+    if bound.is_empty() {
+        return Ok(None);
+    }
+
+    if bound.starts_with(|ch: char| ch.is_ascii_digit()) && bound.contains('-') {
+        let timestamp = if bound.contains(':') {
+            bound.to_string()
+        } else {
+            format!("{} 00:00:00", bound)
+        };
+        return humantime::parse_rfc3339_weak(&timestamp)
+            .map(Some)
+            .map_err(|e| format!("invalid date '{}': {}", bound, e).into());
+    }
+
+    let offset = humantime::parse_duration(bound)
+        .map_err(|e| format!("invalid offset '{}': {}", bound, e))?;
+    SystemTime::now()
+        .checked_sub(offset)
+        .map(Some)
+        .ok_or_else(|| format!("offset '{}' is too far in the past", bound).into())
+}
+
+/// A condition entries must meet, written in the pattern next to the globs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Criterion {
+    /// `+mtime:30d`, modified longer ago than that.
+    OlderThan(Duration),
+    /// `-mtime:7d`, modified more recently than that.
+    NewerThan(Duration),
+    /// `size>100M`, bigger than that many bytes.
+    LargerThan(u64),
+    /// `size<1K`.
+    SmallerThan(u64),
+}
+
+impl Criterion {
+    /// Parses a pattern word, `None` when it isn't a criterion at all.
+    pub fn parse(word: &str) -> Option<Result<Self, ErrorBox>> {
+        if let Some(rest) = word.strip_prefix("size") {
+            let (larger, size) = match (rest.strip_prefix('>'), rest.strip_prefix('<')) {
+                (Some(size), _) => (true, size),
+                (_, Some(size)) => (false, size),
+                _ => return None,
+            };
+            return Some(humanize::parse_size(size).map(|size| match larger {
+                true => Criterion::LargerThan(size),
+                false => Criterion::SmallerThan(size),
+            }));
+        }
+
+        let (older, age) = match (word.strip_prefix("+mtime:"), word.strip_prefix("-mtime:")) {
+            (Some(age), _) => (true, age),
+            (_, Some(age)) => (false, age),
+            _ => return None,
+        };
+        let age = match humantime::parse_duration(age) {
+            Ok(age) => age,
+            Err(e) => return Some(Err(format!("invalid age '{}': {}", age, e).into())),
+        };
+        Some(Ok(match older {
+            true => Criterion::OlderThan(age),
+            false => Criterion::NewerThan(age),
+        }))
+    }
+
+    /// Entries without a known modification time never match an age
+    /// criterion. Directories are sized with everything inside them.
+    pub fn matches(&self, entry: &PathEntry) -> bool {
+        match *self {
+            Criterion::OlderThan(limit) => age(entry).is_some_and(|age| age > limit),
+            Criterion::NewerThan(limit) => age(entry).is_some_and(|age| age < limit),
+            Criterion::LargerThan(limit) => entry.disk_size() > limit,
+            Criterion::SmallerThan(limit) => entry.disk_size() < limit,
+        }
+    }
+}
+
+impl fmt::Display for Criterion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Criterion::OlderThan(age) => {
+                write!(f, "older than {}", humantime::format_duration(age))
+            }
+            Criterion::NewerThan(age) => {
+                write!(f, "newer than {}", humantime::format_duration(age))
+            }
+            Criterion::LargerThan(size) => write!(f, "larger than {}", humanize::size(size)),
+            Criterion::SmallerThan(size) => write!(f, "smaller than {}", humanize::size(size)),
+        }
+    }
+}
+
+/// How long ago the entry was modified, modified in the future counts as now.
+fn age(entry: &PathEntry) -> Option<Duration> {
+    let modified = entry.modified?;
+    Some(
+        SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default(),
+    )
+}
+
+/// Splits the criteria words off a search pattern, like `**/*.zip +mtime:90d size>1G`.
+/// Returns what's left of the pattern, spaced as typed, and the criteria.
+pub fn split_criteria(pattern: &str) -> Result<(String, Vec<Criterion>), ErrorBox> {
+    let mut rest = String::new();
+    let mut criteria = vec![];
+    let mut kept = 0;
+    for word in pattern.split_whitespace() {
+        if let Some(criterion) = Criterion::parse(word) {
+            let start = word.as_ptr() as usize - pattern.as_ptr() as usize;
+            rest.push_str(&pattern[kept..start]);
+            kept = start + word.len();
+            criteria.push(criterion?);
+        }
+    }
+    rest.push_str(&pattern[kept..]);
+    Ok((rest.trim().to_string(), criteria))
+}
@@ -0,0 +1,80 @@
+use crate::{config::Config, ErrorBox};
+use std::fs;
+
+const HISTORY_FILE: &str = "history";
+/// Older patterns are dropped past this many.
+const MAX_ENTRIES: usize = 500;
+
+/// Submitted patterns, oldest first, browsed like a shell history.
+#[derive(Default)]
+pub struct History {
+    pub entries: Vec<String>,
+    /// The entry being shown, `None` while editing a new pattern.
+    position: Option<usize>,
+    /// What was typed before browsing started.
+    draft: String,
+}
+
+impl History {
+    /// Reads the history file from the config directory, empty when there is none.
+    pub fn load() -> Self {
+        let entries = Config::dir()
+            .and_then(|dir| fs::read_to_string(dir.join(HISTORY_FILE)).ok())
+            .map(|content| content.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        History {
+            entries,
+            ..History::default()
+        }
+    }
+
+    pub fn save(&self) -> Result<(), ErrorBox> {
+        let dir = Config::dir().ok_or("no config directory on this platform")?;
+        fs::create_dir_all(&dir)?;
+        let mut content = self.entries.join("\n");
+        content.push('\n');
+        fs::write(dir.join(HISTORY_FILE), content)?;
+        Ok(())
+    }
+
+    /// Adds `pattern` as the newest entry, moving it there when it was
+    /// submitted before, and stops browsing.
+    pub fn push(&mut self, pattern: &str) {
+        self.position = None;
+        if pattern.is_empty() {
+            return;
+        }
+        self.entries.retain(|entry| entry != pattern);
+        self.entries.push(pattern.to_string());
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+
+    /// The entry before the one shown. `current` is kept to come back to.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let position = match self.position {
+            Some(0) => return None,
+            Some(i) => i - 1,
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+        };
+        self.position = Some(position);
+        Some(&self.entries[position])
+    }
+
+    /// The entry after the one shown, or what was typed before browsing
+    /// once past the newest.
+    pub fn newer(&mut self) -> Option<&str> {
+        let position = self.position?;
+        if position + 1 < self.entries.len() {
+            self.position = Some(position + 1);
+            return Some(&self.entries[position + 1]);
+        }
+        self.position = None;
+        Some(&self.draft)
+    }
+}
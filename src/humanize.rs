@@ -0,0 +1,169 @@
+use crate::ErrorBox;
+use std::{fs::Permissions, ops::Range, time::SystemTime};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+const SIZE_UNITS: [&str; 5] = ["K", "M", "G", "T", "P"];
+
+/// Cuts `path` down to `width` columns with `ellipsis` in the middle, keeping
+/// its start and the file name. A file name too long by itself keeps its end,
+/// where the extension is.
+pub fn truncate_path(path: &str, width: usize, ellipsis: &str) -> String {
+    match path_cut(path, width, ellipsis) {
+        Some((cut, true)) => format!("{}{}{}", &path[..cut.start], ellipsis, &path[cut.end..]),
+        Some((cut, false)) => format!("{}{}", &path[..cut.start], &path[cut.end..]),
+        None => path.to_string(),
+    }
+}
+
+/// The bytes of `path` that `truncate_path` leaves out, and whether the
+/// ellipsis takes their place. None when the path fits.
+pub fn path_cut(path: &str, width: usize, ellipsis: &str) -> Option<(Range<usize>, bool)> {
+    if path.width() <= width {
+        return None;
+    }
+    let room = width.saturating_sub(ellipsis.width());
+    if room == 0 {
+        return Some((0..path.len() - take_end(path, width).len(), false));
+    }
+
+    let name = match path.rfind(['/', '\\']) {
+        Some(i) => &path[i..],
+        None => path,
+    };
+    if name.width() >= room {
+        return Some((0..path.len() - take_end(name, room).len(), true));
+    }
+    let start = take_start(path, room - name.width());
+    Some((start.len()..path.len() - name.len(), true))
+}
+
+/// The longest start of `s` at most `width` columns wide.
+fn take_start(s: &str, width: usize) -> &str {
+    let mut taken = 0;
+    for (i, ch) in s.char_indices() {
+        taken += ch.width().unwrap_or(0);
+        if taken > width {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+/// The longest end of `s` at most `width` columns wide.
+fn take_end(s: &str, width: usize) -> &str {
+    let mut taken = 0;
+    for (i, ch) in s.char_indices().rev() {
+        taken += ch.width().unwrap_or(0);
+        if taken > width {
+            return &s[i + ch.len_utf8()..];
+        }
+    }
+    s
+}
+
+/// Formats a byte count like `512B`, `1.5K` or `20.0G`.
+pub fn size(bytes: u64) -> String {
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = SIZE_UNITS[0];
+    for next in SIZE_UNITS.iter().skip(1) {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+
+    format!("{:.1}{}", value, unit)
+}
+
+/// Formats a count with thousands separators, like `113,482`.
+pub fn count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut formatted = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// Parses a byte count written like `size` formats it: `512`, `512B`,
+/// `1.5K`, `100M`, `2G`...
+pub fn parse_size(input: &str) -> Result<u64, ErrorBox> {
+    let input = input.trim();
+    let (number, unit) = match input.find(|ch: char| ch.is_ascii_alphabetic()) {
+        Some(i) => input.split_at(i),
+        None => (input, "B"),
+    };
+
+    let number: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", input))?;
+    let unit = unit.trim().to_ascii_uppercase();
+    let unit = unit.trim_end_matches("IB").trim_end_matches('B');
+    let exponent = match unit {
+        "" => 0,
+        unit => match SIZE_UNITS.iter().position(|u| *u == unit) {
+            Some(i) => i as i32 + 1,
+            None => return Err(format!("unknown size unit in '{}'", input).into()),
+        },
+    };
+
+    if number < 0.0 {
+        return Err(format!("negative size '{}'", input).into());
+    }
+    Ok((number * 1024f64.powi(exponent)) as u64)
+}
+
+/// Formats how long ago `time` was, like `42s ago` or `3d ago`.
+pub fn age(time: SystemTime) -> String {
+    let secs = match SystemTime::now().duration_since(time) {
+        Ok(elapsed) => elapsed.as_secs(),
+        Err(_) => return String::from("in the future"),
+    };
+
+    let (value, unit) = match secs {
+        0..=59 => (secs, "s"),
+        60..=3599 => (secs / 60, "m"),
+        3600..=86_399 => (secs / 3600, "h"),
+        86_400..=2_591_999 => (secs / 86_400, "d"),
+        2_592_000..=31_535_999 => (secs / 2_592_000, "mo"),
+        _ => (secs / 31_536_000, "y"),
+    };
+
+    format!("{}{} ago", value, unit)
+}
+
+/// Formats permissions as `rwxr-xr-x` on unix, `r-` or `rw` elsewhere.
+#[cfg(unix)]
+pub fn permissions(permissions: &Permissions) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = permissions.mode();
+    let flags = ['r', 'w', 'x'];
+    (0..9)
+        .map(|i| {
+            if mode & (1 << (8 - i)) != 0 {
+                flags[i % 3]
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(not(unix))]
+pub fn permissions(permissions: &Permissions) -> String {
+    if permissions.readonly() {
+        String::from("r-")
+    } else {
+        String::from("rw")
+    }
+}
@@ -0,0 +1,382 @@
+use crate::{
+    config::{Column, Density},
+    event::AppEvent,
+    open::Opener,
+    App, AppMode, ErrorBox, PromptKind,
+};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use std::time::Instant;
+use unicode_width::UnicodeWidthStr;
+
+impl App {
+    /// Applies one event to the app, leaving the terminal to the caller.
+    /// Returns whether the screen needs a redraw, idle ticks and mouse moves
+    /// leave it as it is.
+    pub fn handle_event(&mut self, event: AppEvent) -> Result<bool, ErrorBox> {
+        match event {
+            AppEvent::Tick => self.tick(),
+            AppEvent::Input(Event::Mouse(mouse)) => {
+                if mouse.kind == MouseEventKind::Moved {
+                    return Ok(false);
+                }
+                if self.popup.is_none() && !self.is_deleting() {
+                    if let AppMode::Normal = self.app_mode {
+                        self.handle_mouse(mouse);
+                    }
+                }
+                Ok(true)
+            }
+            AppEvent::Input(Event::Key(key))
+                if key.code == KeyCode::Char('c')
+                    && key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                // raw mode turns Ctrl+C into a key instead of SIGINT
+                self.interrupt();
+                Ok(true)
+            }
+            AppEvent::Input(Event::Key(key)) => {
+                self.handle_key(key)?;
+                Ok(true)
+            }
+            AppEvent::Interrupt => {
+                self.interrupt();
+                Ok(true)
+            }
+            AppEvent::Input(_) => Ok(true),
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Result<(), ErrorBox> {
+        if self.popup.is_some() || self.pattern_diff.is_some() || self.tree_diff.is_some() {
+            if let Some(lines) = self.delete_summary.take() {
+                if key.code == KeyCode::Char('e') {
+                    self.export_summary(&lines);
+                }
+            }
+            self.popup = None;
+            self.pattern_diff = None;
+            self.tree_diff = None;
+            return Ok(());
+        }
+
+        // nothing to do but wait until the archive is written
+        if self.archiver.is_some() {
+            return Ok(());
+        }
+
+        // only cancelling is possible until the deletion is done
+        if self.is_deleting() {
+            if key.code == KeyCode::Esc {
+                self.cancel_delete();
+            }
+            return Ok(());
+        }
+
+        match self.app_mode {
+            AppMode::Normal => {
+                // a leading 0 is not a count
+                if let KeyCode::Char(ch @ '0'..='9') = key.code {
+                    if ch != '0' || self.count.is_some() {
+                        self.push_count_digit(ch.to_digit(10).unwrap_or(0));
+                        return Ok(());
+                    }
+                }
+
+                // gg and G take the count as a line number, dd as the number
+                // of entries to delete
+                match (self.pending_key.take(), key.code) {
+                    (Some('d'), code) if key_matches(&self.config.keymap.delete, code) => {
+                        let count = self.take_count();
+                        self.mark_and_delete(count);
+                        return Ok(());
+                    }
+                    (Some('g'), KeyCode::Char('g')) => {
+                        self.jump_to(false);
+                        return Ok(());
+                    }
+                    (None, KeyCode::Char('g')) => {
+                        self.pending_key = Some('g');
+                        return Ok(());
+                    }
+                    (_, KeyCode::Char('G')) => {
+                        self.jump_to(true);
+                        return Ok(());
+                    }
+                    // the count went with the d
+                    (Some('d'), _) => self.count = None,
+                    _ => {}
+                }
+
+                let count = self.take_count();
+                // before r refreshes
+                if key.code == KeyCode::Char('r') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    self.redo_marks();
+                    return Ok(());
+                }
+                let keymap = &self.config.keymap;
+                if key_matches(&keymap.toggle, key.code) {
+                    self.toggle_delete_many(count);
+                } else if key_matches(&keymap.down, key.code) {
+                    (0..count).for_each(|_| self.list.next());
+                } else if key_matches(&keymap.up, key.code) {
+                    (0..count).for_each(|_| self.list.previous());
+                } else if key_matches(&keymap.delete, key.code) {
+                    self.request_delete(self.permanent_delete());
+                    // a second press deletes the selected entries instead,
+                    // as many as counted
+                    self.pending_key = Some('d');
+                    self.count = Some(count).filter(|count| *count > 1);
+                } else if key_matches(&keymap.insert, key.code) {
+                    self.set_app_mode(AppMode::Insert);
+                } else if key_matches(&keymap.quit, key.code) {
+                    self.request_quit();
+                } else {
+                    match key.code {
+                        KeyCode::Char(']') => (0..count).for_each(|_| self.select_next_dir()),
+                        KeyCode::Char('[') => (0..count).for_each(|_| self.select_prev_dir()),
+                        KeyCode::Right | KeyCode::Char('l') => self.expand_selected_dir()?,
+                        KeyCode::Left | KeyCode::Char('h') => self.collapse_selected(),
+                        KeyCode::Char('b') => self.toggle_tree_view(),
+                        KeyCode::Char('F') => self.find_duplicates(),
+                        KeyCode::Char('L') => self.load_more_results(),
+                        KeyCode::Char('~') => self.toggle_case_sensitive(),
+                        KeyCode::PageDown => self.list.page_down(count * self.page_size),
+                        KeyCode::PageUp => self.list.page_up(count * self.page_size),
+                        KeyCode::Home => self.list.first(),
+                        KeyCode::End => self.list.last(),
+                        KeyCode::Char('e') => self.cycle_engine(),
+                        KeyCode::Char('R') => self.reset_search_options()?,
+                        KeyCode::Char('r') => self.refresh()?,
+                        KeyCode::Char('w') => self.toggle_watch(),
+                        KeyCode::Char('f') => self.toggle_respect_ignore(),
+                        KeyCode::Esc if self.search.is_none() && self.command.is_none() => {
+                            self.command_log.clear();
+                        }
+                        KeyCode::Esc => self.cancel_search(),
+                        KeyCode::Char('x') => self.open_prompt(PromptKind::Command),
+                        KeyCode::Char(':') => self.set_app_mode(AppMode::Command),
+                        KeyCode::Char('V') => self.start_visual(),
+                        KeyCode::Char('m') => self.cycle_mark_view(),
+                        KeyCode::Char('c') => self.open_prompt(PromptKind::Root),
+                        KeyCode::Char('t') => self.open_prompt(PromptKind::TimeRange),
+                        KeyCode::Char('H') => self.toggle_histogram(),
+                        KeyCode::Char('T') => self.cycle_theme(),
+                        KeyCode::Tab => self.toggle_preview(),
+                        KeyCode::Char('s') => self.cycle_sort(),
+                        KeyCode::Char('S') => self.toggle_keep_sorted(),
+                        KeyCode::Char('-') => self.toggle_sort_direction(),
+                        KeyCode::Char('!') => self.show_delete_command(),
+                        KeyCode::Char('p') => self.open_presets(),
+                        KeyCode::Char('Q') => self.open_quarantine(),
+                        KeyCode::Char('B') => self.request_archive(),
+                        KeyCode::Char('P') => self.open_prompt(PromptKind::PresetName),
+                        KeyCode::Char('/') => self.open_prompt(PromptKind::Filter),
+                        KeyCode::Char('C') => self.open_prompt(PromptKind::ComparePattern),
+                        KeyCode::Char('?') => self.toggle_help(),
+                        KeyCode::Char('z') => self.toggle_density(),
+                        KeyCode::Char('<') => self.resize_split(-5),
+                        KeyCode::Char('>') => self.resize_split(5),
+                        KeyCode::Char('X') => self.request_delete(true),
+                        KeyCode::Char('u') => self.undo(),
+                        KeyCode::Char('n') => self.toggle_dry_run(),
+                        KeyCode::Char('a') => self.mark_all(),
+                        KeyCode::Char('A') => self.unmark_all(),
+                        KeyCode::Char('v') => self.invert_marks(),
+                        KeyCode::Char('I') => self.show_inspector(),
+                        KeyCode::Char('o') => self.request_open(Opener::Editor),
+                        KeyCode::Char('O') => self.request_open(Opener::System),
+                        KeyCode::Char('y') => self.yank_selected(),
+                        KeyCode::Char('Y') => self.yank_marked(),
+                        KeyCode::Char('E') => self.open_prompt(PromptKind::Export),
+                        KeyCode::Char('W') => self.open_prompt(PromptKind::ExportMarked),
+                        KeyCode::Char('D') => self.show_tree_diff(),
+                        _ => {}
+                    }
+                }
+            }
+            AppMode::Insert => match key.code {
+                KeyCode::Char(ch) => {
+                    self.push_ch(ch);
+                    self.last_edit = Some(Instant::now());
+                }
+                KeyCode::Enter => {
+                    self.last_edit = None;
+                    self.submit_pattern()?;
+                    self.set_app_mode(AppMode::Normal);
+                }
+                KeyCode::Up => {
+                    self.history_previous();
+                    self.last_edit = Some(Instant::now());
+                }
+                KeyCode::Down => {
+                    self.history_next();
+                    self.last_edit = Some(Instant::now());
+                }
+                KeyCode::Backspace => {
+                    self.pop_ch();
+                    self.last_edit = Some(Instant::now());
+                }
+                KeyCode::Esc => {
+                    self.set_app_mode(AppMode::Normal);
+                }
+                _ => {}
+            },
+            AppMode::Prompt => match key.code {
+                KeyCode::Char(ch) => {
+                    if let Some(prompt) = self.prompt.as_mut() {
+                        prompt.input.push_ch(ch);
+                    }
+                }
+                KeyCode::Backspace => {
+                    if let Some(prompt) = self.prompt.as_mut() {
+                        prompt.input.pop_ch();
+                    }
+                }
+                KeyCode::Enter => self.submit_prompt()?,
+                KeyCode::Esc => self.close_prompt(),
+                _ => {}
+            },
+            AppMode::Visual => {
+                if let KeyCode::Char(ch @ '0'..='9') = key.code {
+                    if ch != '0' || self.count.is_some() {
+                        self.push_count_digit(ch.to_digit(10).unwrap_or(0));
+                        return Ok(());
+                    }
+                }
+                match (self.pending_key.take(), key.code) {
+                    (Some('g'), KeyCode::Char('g')) => self.jump_to(false),
+                    (None, KeyCode::Char('g')) => self.pending_key = Some('g'),
+                    (_, KeyCode::Char('G')) => self.jump_to(true),
+                    (_, code) => {
+                        let count = self.take_count();
+                        let keymap = &self.config.keymap;
+                        if key_matches(&keymap.down, code) {
+                            (0..count).for_each(|_| self.list.next());
+                        } else if key_matches(&keymap.up, code) {
+                            (0..count).for_each(|_| self.list.previous());
+                        } else if key_matches(&keymap.delete, code) {
+                            self.end_visual(true);
+                            self.request_delete(self.permanent_delete());
+                        } else {
+                            match code {
+                                KeyCode::PageDown => self.list.page_down(count * self.page_size),
+                                KeyCode::PageUp => self.list.page_up(count * self.page_size),
+                                KeyCode::Home => self.list.first(),
+                                KeyCode::End => self.list.last(),
+                                KeyCode::Char('V') | KeyCode::Enter => self.end_visual(true),
+                                KeyCode::Esc => self.end_visual(false),
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                if let AppMode::Visual = self.app_mode {
+                    self.update_visual();
+                }
+            }
+            AppMode::Command => match key.code {
+                KeyCode::Char(ch) => self.command_line.push_ch(ch),
+                // like in vi, erasing past the start leaves the command line
+                KeyCode::Backspace if self.command_line.content.is_empty() => {
+                    self.set_app_mode(AppMode::Normal);
+                }
+                KeyCode::Backspace => self.command_line.pop_ch(),
+                KeyCode::Enter => {
+                    let line = std::mem::take(&mut self.command_line.content);
+                    self.set_app_mode(AppMode::Normal);
+                    self.run_command_line(&line)?;
+                }
+                KeyCode::Esc => {
+                    self.command_line.content.clear();
+                    self.set_app_mode(AppMode::Normal);
+                }
+                _ => {}
+            },
+            AppMode::Confirm => {
+                let pending_key = self.pending_key.take();
+                let count = self.take_count();
+                let keymap = &self.config.keymap;
+                if key_matches(&keymap.confirm, key.code) {
+                    self.accept_confirm()?;
+                } else if key_matches(&keymap.cancel, key.code) {
+                    self.cancel_confirm()?;
+                } else if pending_key == Some('d') && key_matches(&keymap.delete, key.code) {
+                    // the first d asked about the marked entries
+                    self.confirm = None;
+                    self.set_app_mode(AppMode::Normal);
+                    self.mark_and_delete(count);
+                }
+            }
+            AppMode::Presets => match key.code {
+                KeyCode::Down | KeyCode::Char('j') => self.presets.next(),
+                KeyCode::Up | KeyCode::Char('k') => self.presets.previous(),
+                KeyCode::Enter => self.apply_selected_preset()?,
+                KeyCode::Esc | KeyCode::Char('q') => self.set_app_mode(AppMode::Normal),
+                _ => {}
+            },
+            AppMode::Quarantine => match key.code {
+                KeyCode::Down | KeyCode::Char('j') => self.quarantine.next(),
+                KeyCode::Up | KeyCode::Char('k') => self.quarantine.previous(),
+                KeyCode::Enter | KeyCode::Char('r') => self.restore_selected_batch()?,
+                KeyCode::Char('x') => self.request_purge(),
+                KeyCode::Esc | KeyCode::Char('q') => self.set_app_mode(AppMode::Normal),
+                _ => {}
+            },
+        }
+
+        Ok(())
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => self.list.page_down(1),
+            MouseEventKind::ScrollUp => self.list.page_up(1),
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some((index, first_row)) = self.entry_at(mouse.column, mouse.row) {
+                    let entry = &self.list.items[index];
+                    // compact rows start with a one character marker
+                    let (marker_start, marker_width) = match self.density() {
+                        Density::Compact => (self.list_area.x, 1),
+                        Density::Comfortable | Density::Dense => (
+                            self.list_area.x
+                                + self
+                                    .config
+                                    .columns
+                                    .iter()
+                                    .filter(|column| **column != Column::Path)
+                                    .map(|column| {
+                                        self.column_text(entry, *column).width() as u16 + 1
+                                    })
+                                    .sum::<u16>(),
+                            6,
+                        ),
+                    };
+                    let on_marker = first_row
+                        && (marker_start..marker_start + marker_width).contains(&mouse.column);
+                    self.click_entry(index, on_marker);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether `code` is one of `keys`, as written in the keymap of the config.
+fn key_matches(keys: &[String], code: KeyCode) -> bool {
+    let name = match code {
+        KeyCode::Char(ch) => return keys.iter().any(|key| key.chars().eq([ch])),
+        KeyCode::Enter => "Enter",
+        KeyCode::Esc => "Esc",
+        KeyCode::Tab => "Tab",
+        KeyCode::Backspace => "Backspace",
+        KeyCode::Delete => "Delete",
+        KeyCode::Left => "Left",
+        KeyCode::Right => "Right",
+        KeyCode::Up => "Up",
+        KeyCode::Down => "Down",
+        _ => return false,
+    };
+    keys.iter().any(|key| key.eq_ignore_ascii_case(name))
+}
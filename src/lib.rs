@@ -1,31 +1,345 @@
-use glob::{glob_with, MatchOptions};
-use std::{error::Error, fs, path::PathBuf};
-use tui::{
-    style::{Color, Style},
-    widgets::ListState,
+pub mod archive;
+pub mod command;
+pub mod config;
+pub mod delete;
+pub mod duplicates;
+#[cfg(feature = "tui")]
+pub mod event;
+pub mod ex;
+pub mod export;
+pub mod filter;
+pub mod history;
+pub mod humanize;
+#[cfg(feature = "tui")]
+mod keys;
+pub mod log;
+pub mod open;
+pub mod presets;
+pub mod preview;
+pub mod protect;
+pub mod quarantine;
+pub mod search;
+pub mod sizes;
+pub mod theme;
+pub mod ui;
+pub mod undo;
+pub mod watch;
+
+use archive::Archiver;
+use command::{CommandMessage, CommandRun};
+use config::{Column, Config, Density, QuitConfirm};
+use delete::{DeleteMessage, Deletion, Disposal};
+use duplicates::DuplicateFinder;
+use ex::ExCommand;
+use export::{DryRunDocument, ExportedEntry};
+use filter::{split_criteria, Criterion, TimeRange};
+use globset::{GlobBuilder, GlobSet};
+use history::History;
+use log::{AuditRecord, Outcome};
+use open::Opener;
+use presets::Preset;
+use regex::RegexBuilder;
+use search::{Globs, Highlighter, MatchEngine, Search, SearchMessage};
+use sizes::SizeScanner;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    env,
+    error::Error,
+    fmt,
+    fs::{self, FileType, Permissions},
+    io,
+    ops::Range,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
 };
+use theme::ThemeName;
+pub use ui::list::StatefulList;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use walkdir::WalkDir;
+use watch::RootWatcher;
+
+/// Two clicks on the same entry closer than this toggle it.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+/// Typing pauses this long before the live search runs.
+const LIVE_SEARCH_DELAY: Duration = Duration::from_millis(300);
+/// Lines of command output kept for the log pane.
+const COMMAND_LOG_LINES: usize = 1000;
+/// Marking changes kept to undo.
+const MARK_UNDO_STEPS: usize = 100;
 
 pub enum AppMode {
     Normal,
     Insert,
+    Prompt,
+    Presets,
+    Confirm,
+    /// Browsing the quarantined batches.
+    Quarantine,
+    /// Typing a command on the `:` command line.
+    Command,
+    /// Marking the entries between where `V` was pressed and the selection.
+    Visual,
+}
+
+impl AppMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppMode::Normal => "NORMAL",
+            AppMode::Insert => "INSERT",
+            AppMode::Prompt => "PROMPT",
+            AppMode::Presets => "PRESETS",
+            AppMode::Quarantine => "QUARANTINE",
+            AppMode::Confirm => "CONFIRM",
+            AppMode::Command => "COMMAND",
+            AppMode::Visual => "VISUAL",
+        }
+    }
+}
+
+/// An action waiting for the user to confirm it.
+pub enum ConfirmAction {
+    /// Delete the marked entries, `count` of them. Their size is shown as
+    /// it's known, see `App::marked_size_of`.
+    Delete {
+        permanent: bool,
+        count: usize,
+        /// Only these of the marked entries, like the ones `dd` marked.
+        only: Option<HashSet<PathBuf>>,
+    },
+    /// Delete the next directory of a batch paused by `per_dir_confirm`.
+    DeleteDir {
+        path: PathBuf,
+        children: u64,
+        size: u64,
+    },
+    Quit,
+    /// Replace the config preset with the same name.
+    OverwritePreset(Preset),
+    /// Delete a quarantined batch for good.
+    PurgeQuarantine(quarantine::Batch),
+    /// Archive the marked entries, then delete them.
+    Archive {
+        count: usize,
+    },
+}
+
+/// Marked entries being deleted, kept around so the batch can pause for input.
+pub struct DeleteBatch {
+    pending: VecDeque<PathEntry>,
+    /// Entries deleted so far, with the bytes of regular files they held.
+    removed: HashMap<PathBuf, u64>,
+    /// Bytes of regular files deleted so far.
+    freed: u64,
+    disposal: Disposal,
+    /// The archive of the entries, written before deleting them.
+    archive: Option<PathBuf>,
+    /// Entries that could not be deleted, with the error.
+    failed: Vec<(PathBuf, String)>,
+    /// Entries in the batch when it started.
+    total: usize,
+    started: Instant,
+    /// The entry being deleted right now.
+    current: Option<PathBuf>,
+    /// Deletes a run of pending entries, `None` while waiting for a confirmation.
+    deletion: Option<Deletion>,
+    cancelled: bool,
+}
+
+impl DeleteBatch {
+    /// Whether entries are being deleted right now.
+    pub fn is_running(&self) -> bool {
+        self.deletion.is_some()
+    }
+
+    /// Entries deleted or failed so far.
+    pub fn done(&self) -> usize {
+        self.removed.len() + self.failed.len()
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn freed(&self) -> u64 {
+        self.freed
+    }
+
+    pub fn current(&self) -> Option<&Path> {
+        self.current.as_deref()
+    }
+
+    /// Takes in one message from the worker, returns whether it was the last.
+    fn apply(&mut self, message: DeleteMessage) -> bool {
+        match message {
+            DeleteMessage::Removing(path) => self.current = Some(path),
+            DeleteMessage::Removed(path, size) => {
+                self.freed += size;
+                self.removed.insert(path, size);
+            }
+            DeleteMessage::Failed(path, err) => self.failed.push((path, err)),
+            DeleteMessage::Done => return true,
+        }
+        false
+    }
+
+    /// What was and wasn't deleted, one line each, then the failures.
+    pub fn report(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "Eradicated {} of {} entries, {}",
+                self.removed.len(),
+                self.total,
+                humanize::size(self.freed)
+            ),
+            format!(
+                "Left untouched: {} entries",
+                self.total.saturating_sub(self.done())
+            ),
+        ];
+        for (path, err) in &self.failed {
+            lines.push(format!("Failed: {}: {}", path.display(), err));
+        }
+        lines
+    }
+
+    /// A record of each entry deleted or failed, for the audit log.
+    pub fn audit_records(&self, time: SystemTime) -> Vec<AuditRecord> {
+        let outcome = match self.disposal {
+            Disposal::Trash => Outcome::Trashed,
+            Disposal::Quarantine { .. } => Outcome::Quarantined,
+            Disposal::Permanent => Outcome::Deleted,
+        };
+        let mut removed: Vec<(&PathBuf, &u64)> = self.removed.iter().collect();
+        removed.sort();
+        let mut records: Vec<AuditRecord> = removed
+            .into_iter()
+            .map(|(path, size)| AuditRecord::new(time, path.clone(), Some(*size), outcome))
+            .collect();
+        for (path, err) in &self.failed {
+            let mut record = AuditRecord::new(time, path.clone(), None, Outcome::Failed);
+            record.error = Some(err.clone());
+            records.push(record);
+        }
+        records
+    }
+
+    /// The report with how long the batch took, shown once it's done.
+    pub fn summary(&self) -> Vec<String> {
+        let mut lines = self.report();
+        let elapsed = Duration::from_millis(self.started.elapsed().as_millis() as u64);
+        lines.insert(1, format!("Took {}", humantime::format_duration(elapsed)));
+        let destination = match self.disposal {
+            Disposal::Trash => "Moved to the trash",
+            Disposal::Quarantine { .. } => "Moved to the quarantine",
+            Disposal::Permanent => "Deleted for good",
+        };
+        lines.insert(2, String::from(destination));
+        if let Some(archive) = &self.archive {
+            lines.insert(3, format!("Archived to {}", archive.display()));
+        }
+        lines
+    }
+
+    /// Time left at the pace of the entries done so far.
+    pub fn eta(&self) -> Option<Duration> {
+        let done = self.done() as u32;
+        if done == 0 {
+            return None;
+        }
+        let left = self.total.saturating_sub(self.done()) as u32;
+        Some(self.started.elapsed() / done * left)
+    }
 }
 
 pub type ErrorBox = Box<dyn Error>;
 
+/// The search root was removed or can no longer be read.
+#[derive(Debug)]
+pub struct RootError(pub PathBuf);
+
+impl fmt::Display for RootError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "search root no longer accessible: {}", self.0.display())
+    }
+}
+
+impl Error for RootError {}
+
+/// A pattern that doesn't parse, with the part of it at fault.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternError {
+    pub message: String,
+    /// Bytes of the pattern at fault.
+    pub span: Range<usize>,
+}
+
+impl PatternError {
+    pub fn new(message: impl Into<String>, span: Range<usize>) -> Self {
+        PatternError {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// The same error for a pattern found `offset` bytes into a longer one.
+    pub fn shifted(self, offset: usize) -> Self {
+        let span = self.span.start + offset..self.span.end + offset;
+        PatternError { span, ..self }
+    }
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for PatternError {}
+
+/// A rectangle of terminal cells, as laid out by whoever draws the app.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Area {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Area {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Area {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The first column past the area.
+    pub fn right(&self) -> u16 {
+        self.x.saturating_add(self.width)
+    }
+
+    /// The first row past the area.
+    pub fn bottom(&self) -> u16 {
+        self.y.saturating_add(self.height)
+    }
+}
+
 pub struct Input {
     pub name: String,
     pub content: String,
-    pub active_style: Style,
-    pub normal_style: Style,
+    /// Columns of the content scrolled out on the left.
+    pub scroll: usize,
 }
 
 impl Input {
-    pub fn new(name: &str, active_style: Style, normal_style: Style) -> Self {
+    pub fn new(name: &str) -> Self {
         Input {
             name: name.to_string(),
             content: String::new(),
-            active_style,
-            normal_style,
+            scroll: 0,
         }
     }
     pub fn push_ch(&mut self, ch: char) {
@@ -35,26 +349,468 @@ impl Input {
     pub fn pop_ch(&mut self) {
         self.content.pop();
     }
+
+    /// Scrolls so the cursor, always at the end of the content, is visible
+    /// in `width` columns with as much of the content as fits before it.
+    pub fn scroll_to_cursor(&mut self, width: usize) {
+        self.scroll = (self.content.width() + 1).saturating_sub(width);
+    }
+
+    /// The content after `scroll` and the cursor column relative to it. Wide
+    /// characters cut by the left edge are hidden whole.
+    pub fn visible(&self) -> (&str, usize) {
+        let mut skipped = 0;
+        let mut start = self.content.len();
+        for (i, ch) in self.content.char_indices() {
+            if skipped >= self.scroll {
+                start = i;
+                break;
+            }
+            skipped += ch.width().unwrap_or(0);
+        }
+        (&self.content[start..], self.content.width() - skipped)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Size,
+    Mtime,
+    Type,
+}
+
+impl SortKey {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Size => "size",
+            SortKey::Mtime => "mtime",
+            SortKey::Type => "type",
+        }
+    }
+
+    /// The key called `name`, the other way around from `name`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [SortKey::Name, SortKey::Size, SortKey::Mtime, SortKey::Type]
+            .into_iter()
+            .find(|key| key.name() == name)
+    }
+
+    pub fn compare(&self, a: &PathEntry, b: &PathEntry) -> Ordering {
+        match self {
+            SortKey::Name => a.pathbuf.cmp(&b.pathbuf),
+            SortKey::Size => a.size.cmp(&b.size),
+            SortKey::Mtime => a.modified.cmp(&b.modified),
+            SortKey::Type => b
+                .is_dir
+                .cmp(&a.is_dir)
+                .then_with(|| a.pathbuf.cmp(&b.pathbuf)),
+        }
+    }
+
+    /// Like `compare`, reversed when `descending`.
+    pub fn compare_ordered(&self, a: &PathEntry, b: &PathEntry, descending: bool) -> Ordering {
+        let ordering = self.compare(a, b);
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    /// The next key when cycling, `None` going back to the search order.
+    fn cycle(sort: Option<SortKey>) -> Option<SortKey> {
+        match sort {
+            None => Some(SortKey::Name),
+            Some(SortKey::Name) => Some(SortKey::Size),
+            Some(SortKey::Size) => Some(SortKey::Mtime),
+            Some(SortKey::Mtime) => Some(SortKey::Type),
+            Some(SortKey::Type) => None,
+        }
+    }
+}
+
+/// Which entries the result list shows, by how they're marked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkView {
+    All,
+    Marked,
+    Unmarked,
+}
+
+impl MarkView {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MarkView::All => "all",
+            MarkView::Marked => "marked",
+            MarkView::Unmarked => "unmarked",
+        }
+    }
+
+    fn shows(&self, entry: &PathEntry) -> bool {
+        match self {
+            MarkView::All => true,
+            MarkView::Marked => entry.is_delete(),
+            MarkView::Unmarked => !entry.is_delete(),
+        }
+    }
+
+    fn cycle(self) -> Self {
+        match self {
+            MarkView::All => MarkView::Marked,
+            MarkView::Marked => MarkView::Unmarked,
+            MarkView::Unmarked => MarkView::All,
+        }
+    }
+}
+
+/// Matches of two patterns, split by which pattern found them.
+pub struct PatternDiff {
+    pub pattern_a: String,
+    pub pattern_b: String,
+    pub only_a: Vec<PathBuf>,
+    pub only_b: Vec<PathBuf>,
+    pub both: Vec<PathBuf>,
+}
+
+/// What deleting the marked entries would remove.
+pub struct DryRunReport {
+    /// Marked paths with their size, directory content included.
+    pub entries: Vec<(PathBuf, u64)>,
+    pub files: usize,
+    pub dirs: usize,
+    pub total_size: u64,
+}
+
+impl DryRunReport {
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .entries
+            .iter()
+            .map(|(path, size)| format!("{:>8}  {}", humanize::size(*size), path.display()))
+            .collect();
+        lines.push(format!(
+            "Would eradicate {} files and {} directories, {} in total",
+            self.files,
+            self.dirs,
+            humanize::size(self.total_size)
+        ));
+        lines
+    }
+}
+
+/// A directory holding marked entries, listed before and after deleting them.
+pub struct DirDiff {
+    pub dir: PathBuf,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// A read-only message shown over the interface until the next key press.
+pub struct Popup {
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+/// Everything known about a listed entry, shown by the inspector.
+pub struct EntryDetails {
+    pub path: PathBuf,
+    pub kind: &'static str,
+    /// The size the Size column shows, `None` for special files, which are
+    /// never read, and directories not sized yet.
+    pub size: Option<u64>,
+    pub modified: Option<SystemTime>,
+    pub permissions: Option<Permissions>,
+    pub symlink_target: Option<PathBuf>,
+    /// Engine and pattern of the search that listed the entry.
+    pub matched_by: Option<(MatchEngine, String)>,
+    pub case_sensitive: bool,
+    pub marked: bool,
+    pub outside_root: bool,
+    pub other_filesystem: bool,
+}
+
+pub enum PromptKind {
+    Root,
+    TimeRange,
+    ComparePattern,
+    PresetName,
+    Filter,
+    Command,
+    Export,
+    ExportMarked,
+}
+
+/// A one-off input shown in a popup, submitted with Enter.
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: Input,
+}
+
+impl Prompt {
+    pub fn new(kind: PromptKind) -> Self {
+        let name = match kind {
+            PromptKind::Root => "New search root",
+            PromptKind::TimeRange => "Modified in range (start..end, ! for outside)",
+            PromptKind::ComparePattern => "Compare the current pattern with",
+            PromptKind::PresetName => "Save the current search as preset",
+            PromptKind::Filter => "Filter the results (empty to clear)",
+            PromptKind::Command => "Run on the marked entries, their paths appended",
+            PromptKind::Export => "Export the results to (.json for JSON)",
+            PromptKind::ExportMarked => "Export the marked entries to (.json for JSON)",
+        };
+        Prompt {
+            kind,
+            input: Input::new(name),
+        }
+    }
+}
+
+/// The range marked in visual mode, from `anchor` to the selection.
+struct Visual {
+    anchor: PathBuf,
+    /// How the entries the range went over were marked before, to put them
+    /// back once it leaves them or visual mode is cancelled.
+    before: HashMap<PathBuf, bool>,
 }
 
 pub struct App {
     pub list: StatefulList<PathEntry>,
     pub app_mode: AppMode,
     pub pattern: Input,
-    glob_options: MatchOptions,
+    pub history: History,
+    pub config: Config,
+    /// Colors the interface is drawn with.
+    pub theme: ThemeName,
+    pub root: PathBuf,
+    pub prompt: Option<Prompt>,
+    /// What's typed on the `:` command line.
+    pub command_line: Input,
+    visual: Option<Visual>,
+    pub status: Option<String>,
+    pub popup: Option<Popup>,
+    /// The summary of the last deletion while its popup is shown, to export.
+    pub delete_summary: Option<Vec<String>>,
+    pub sort: Option<SortKey>,
+    pub sort_descending: bool,
+    /// Deleting only reports what would be deleted.
+    pub dry_run: bool,
+    /// Where dry run reports are written, besides the popup.
+    pub dry_run_report: Option<PathBuf>,
+    /// Deletions are for good this run whatever the config says, like with
+    /// `--permanent`. Never saved.
+    pub force_permanent: bool,
+    /// Deletions go unlogged this run whatever the config says, like with
+    /// `--no-log`. Never saved.
+    pub skip_log: bool,
+    /// Deletions go to the quarantine this run whatever the config says,
+    /// like with `--quarantine`. Never saved.
+    pub force_quarantine: bool,
+    /// Whether `respect_ignore` of the config was toggled this run. Never saved.
+    ignore_toggled: bool,
+    /// Whether `watch` of the config was toggled this run. Never saved.
+    watch_toggled: bool,
+    /// Query narrowing down the listed entries, empty when showing all.
+    pub filter: String,
+    /// Narrows down the listed entries to the marked or unmarked ones, along
+    /// with the filter.
+    pub mark_view: MarkView,
+    /// Entries hidden by the filter, with their position in the full list.
+    filtered_out: Vec<(usize, PathEntry)>,
+    pub presets: StatefulList<Preset>,
+    /// Quarantined batches, while the quarantine view is open.
+    pub quarantine: StatefulList<quarantine::Batch>,
+    pub confirm: Option<ConfirmAction>,
+    pub pattern_diff: Option<PatternDiff>,
+    pub tree_diff: Option<Vec<DirDiff>>,
+    pub engine: MatchEngine,
+    pub search: Option<Search>,
+    /// A search is running and more results may still come in.
+    pub searching: bool,
+    pub spinner: usize,
+    pub batch: Option<DeleteBatch>,
+    /// The listed entries were found by an absolute pattern.
+    pub absolute_results: bool,
+    pub watcher: Option<RootWatcher>,
+    /// Content sizes of the listed directories computed so far.
+    pub dir_sizes: HashMap<PathBuf, u64>,
+    /// Directories waiting for their size.
+    pending_sizes: HashSet<PathBuf>,
+    /// Started with the first directory to size.
+    size_scanner: Option<SizeScanner>,
+    /// Engine and pattern of the last search, kept while the pattern is edited.
+    pub last_search: Option<(MatchEngine, String)>,
+    /// Set once the user asked to quit, the event loop stops on it.
+    pub should_quit: bool,
+    /// Printed once the terminal is restored after quitting.
+    pub exit_report: Vec<String>,
+    /// Count typed before a motion or toggle, like the `5` in `5j`.
+    pub count: Option<usize>,
+    /// First key of a two key command, like the first `g` of `gg` or `d`
+    /// of `dd`.
+    pub pending_key: Option<char>,
+    /// Entries fitting in the result list, kept up to date when drawing.
+    pub page_size: usize,
+    /// Index of the first entry in view.
+    pub list_offset: usize,
+    /// Matches past the result cap, listed by `load_more_results`.
+    pub overflow: VecDeque<PathBuf>,
+    /// How many matches may be listed before the rest overflow.
+    result_limit: usize,
+    /// Started by `find_duplicates`, until the groups are in.
+    duplicate_finder: Option<DuplicateFinder>,
+    /// The external command running on the marked entries.
+    command: Option<CommandRun>,
+    /// Writes the archive of the marked entries before they are deleted.
+    pub archiver: Option<Archiver>,
+    /// An entry to open once the interface is out of the way.
+    pub open_request: Option<(PathBuf, Opener)>,
+    /// Text for the interface to put on the system clipboard.
+    pub clipboard: Option<String>,
+    /// What the last external command wrote, for the log pane.
+    pub command_log: Vec<String>,
+    /// Number of the duplicate group each listed copy belongs to, from 1.
+    pub duplicate_groups: HashMap<PathBuf, usize>,
+    /// The time range the results were narrowed down to with `t`.
+    time_range: Option<TimeRange>,
+    /// Results nested under headings for their parent directories.
+    pub tree_view: bool,
+    /// Path selected before `refresh`, selected again once the search is done.
+    reselect: Option<PathBuf>,
+    /// Why entries of the last deletion could not be deleted, kept for when
+    /// a search lists them again.
+    delete_errors: HashMap<PathBuf, String>,
+    /// Entries hidden under a collapsed directory of the tree view.
+    collapsed: HashMap<PathBuf, Vec<PathEntry>>,
+    /// Where the result list rows were drawn, inside its borders.
+    pub list_area: Area,
+    /// Screen rows taken by each listed entry.
+    pub rows_per_entry: u16,
+    /// Whether a density was picked with `z`, large result sets keep it then.
+    density_picked: bool,
+    /// How the user marked or unmarked paths, reapplied when a search lists
+    /// them again.
+    pub mark_decisions: HashMap<PathBuf, bool>,
+    /// Entry clicked last and when, to tell double clicks.
+    last_click: Option<(usize, Instant)>,
+    /// When the pattern was last edited, until the live search catches up.
+    last_edit: Option<Instant>,
+    /// Paths moved to the trash by the last deletion, for `undo_delete`.
+    pub last_trashed: Vec<PathBuf>,
+    /// Marking changes to undo, the latest last, each entry changed with how
+    /// it was marked before.
+    mark_undo: Vec<Vec<(PathBuf, bool)>>,
+    /// Marking changes undone, to redo.
+    mark_redo: Vec<Vec<(PathBuf, bool)>>,
+    /// Preview of the selected entry, with the line limit it was made for.
+    preview: Option<(PathBuf, usize, Vec<String>)>,
+    /// The `!pattern` parts of the last search, results matching any are left out.
+    excludes: GlobSet,
+    /// Conditions of the last search, like `+mtime:30d`, every result meets them all.
+    pub criteria: Vec<Criterion>,
+    /// What the pattern of the last search matched in each result, for the
+    /// engines that match paths.
+    highlighter: Option<Highlighter>,
+    /// Why the pattern could not run, until it's edited or runs.
+    pub pattern_error: Option<PatternError>,
+    case_sensitive: bool,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        App::new()
+    }
 }
 
 impl App {
     pub fn new() -> Self {
+        App::with_config(Config::default())
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        let case_sensitive = config.case_sensitive;
+        let theme = match env::var_os("NO_COLOR") {
+            Some(value) if !value.is_empty() => ThemeName::NoColor,
+            _ => config.theme,
+        };
         App {
             list: StatefulList::new(),
             app_mode: AppMode::Normal,
-            pattern: Input::new(
-                "Pattern",
-                Style::default().fg(Color::Yellow),
-                Style::default(),
-            ),
-            glob_options: MatchOptions::new(),
+            pattern: Input::new("Pattern"),
+            theme,
+            history: History::default(),
+            config,
+            root: env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+            prompt: None,
+            command_line: Input::new(":"),
+            visual: None,
+            status: None,
+            popup: None,
+            delete_summary: None,
+            sort: None,
+            sort_descending: false,
+            dry_run: false,
+            dry_run_report: None,
+            force_permanent: false,
+            skip_log: false,
+            force_quarantine: false,
+            ignore_toggled: false,
+            watch_toggled: false,
+            filter: String::new(),
+            mark_view: MarkView::All,
+            filtered_out: vec![],
+            presets: StatefulList::new(),
+            quarantine: StatefulList::new(),
+            confirm: None,
+            pattern_diff: None,
+            tree_diff: None,
+            engine: MatchEngine::Glob,
+            search: None,
+            searching: false,
+            spinner: 0,
+            batch: None,
+            absolute_results: false,
+            watcher: None,
+            dir_sizes: HashMap::new(),
+            pending_sizes: HashSet::new(),
+            size_scanner: None,
+            last_search: None,
+            should_quit: false,
+            exit_report: vec![],
+            count: None,
+            pending_key: None,
+            page_size: 1,
+            list_offset: 0,
+            overflow: VecDeque::new(),
+            result_limit: 0,
+            duplicate_finder: None,
+            command: None,
+            command_log: vec![],
+            archiver: None,
+            open_request: None,
+            clipboard: None,
+            duplicate_groups: HashMap::new(),
+            time_range: None,
+            tree_view: false,
+            collapsed: HashMap::new(),
+            list_area: Area::default(),
+            rows_per_entry: 1,
+            density_picked: false,
+            mark_decisions: HashMap::new(),
+            reselect: None,
+            delete_errors: HashMap::new(),
+            last_click: None,
+            last_edit: None,
+            last_trashed: vec![],
+            mark_undo: vec![],
+            mark_redo: vec![],
+            preview: None,
+            excludes: GlobSet::empty(),
+            criteria: vec![],
+            highlighter: None,
+            pattern_error: None,
+            case_sensitive,
         }
     }
 
@@ -63,167 +819,3103 @@ impl App {
     }
 
     pub fn push_ch(&mut self, ch: char) {
-        self.pattern.push_ch(ch)
+        self.pattern.push_ch(ch);
+        self.pattern_error = None;
     }
 
     pub fn pop_ch(&mut self) {
-        self.pattern.pop_ch()
+        self.pattern.pop_ch();
+        self.pattern_error = None;
     }
 
-    pub fn set_pattern(&mut self) -> Result<(), ErrorBox> {
-        let entries = self.search_with_pattern()?;
-        self.update_list(entries);
-        Ok(())
+    pub fn push_count_digit(&mut self, digit: u32) {
+        let count = self.count.unwrap_or(0);
+        self.count = Some(count.saturating_mul(10).saturating_add(digit as usize));
     }
 
-    pub fn is_case_sensitive(&self) -> bool {
-        self.glob_options.case_sensitive
+    /// Scrolls the view just enough to show the selected entry, like the
+    /// list widget does, and returns the new offset.
+    pub fn update_list_offset(&mut self) -> usize {
+        let last_offset = self.list.items.len().saturating_sub(self.page_size);
+        let mut offset = self.list_offset.min(last_offset);
+        if let Some(selected) = self.list.get_index() {
+            if selected < offset {
+                offset = selected;
+            } else if selected >= offset + self.page_size {
+                offset = selected + 1 - self.page_size;
+            }
+        }
+        self.list_offset = offset;
+        offset
     }
 
-    pub fn toggle_case_sensitive(&mut self) {
-        self.glob_options.case_sensitive = !self.glob_options.case_sensitive;
+    /// The entry drawn at the given screen cell of the result list, and
+    /// whether the cell is on its first row.
+    pub fn entry_at(&self, column: u16, row: u16) -> Option<(usize, bool)> {
+        let area = self.list_area;
+        if column < area.x || column >= area.right() || row < area.y || row >= area.bottom() {
+            return None;
+        }
+        let rows = self.rows_per_entry.max(1);
+        let index = self.list_offset + ((row - area.y) / rows) as usize;
+        (index < self.list.items.len()).then_some((index, (row - area.y).is_multiple_of(rows)))
     }
 
-    fn search_with_pattern(&self) -> Result<Vec<PathEntry>, ErrorBox> {
-        let entries: Vec<PathEntry> =
-            glob_with(&self.pattern.content, self.glob_options)?
-                .filter_map(Result::ok)
-                .map(PathEntry::new)
-                .collect();
-        Ok(entries)
+    /// Selects the entry at `index`, and toggles it when `toggle` is set or
+    /// when it was clicked moments ago.
+    pub fn click_entry(&mut self, index: usize, toggle: bool) {
+        let now = Instant::now();
+        let double = self
+            .last_click
+            .is_some_and(|(last, at)| last == index && now.duration_since(at) <= DOUBLE_CLICK);
+        self.list.select_index(index);
+        if toggle || double {
+            self.toggle_delete();
+            // a third click starts over
+            self.last_click = None;
+        } else {
+            self.last_click = Some((index, now));
+        }
     }
 
-    fn update_list(&mut self, entries: Vec<PathEntry>) {
-        self.list = StatefulList::with_items(entries);
+    /// Selects the entry numbered by the pending count, counting from 1,
+    /// or the first (or last with `last`) one without a count.
+    pub fn jump_to(&mut self, last: bool) {
+        match self.count.take() {
+            Some(n) => self.list.select_index(n.saturating_sub(1)),
+            None if last => self.list.last(),
+            None => self.list.first(),
+        }
     }
 
-    pub fn toggle_delete(&mut self) {
-        let i = self.list.get_index();
-        if i.is_none() {
-            return;
+    /// The pending count, 1 when none was typed, and clears it.
+    pub fn take_count(&mut self) -> usize {
+        self.count.take().filter(|count| *count > 0).unwrap_or(1)
+    }
+
+    /// Picks up what the workers sent, runs the live search once typing
+    /// paused and advances the spinner. Returns whether anything changed
+    /// on screen, which is while work is in progress.
+    pub fn tick(&mut self) -> Result<bool, ErrorBox> {
+        let busy = self.is_busy();
+        self.poll_workers()?;
+        if self.config.live_search
+            && self
+                .last_edit
+                .is_some_and(|t| t.elapsed() >= LIVE_SEARCH_DELAY)
+        {
+            self.last_edit = None;
+            self.search_live();
         }
+        self.on_tick();
+        Ok(busy || self.is_busy())
+    }
 
-        let i = i.unwrap();
+    /// Runs the pattern. A pattern that doesn't parse is kept in
+    /// `pattern_error` instead, and nothing runs.
+    pub fn set_pattern(&mut self) -> Result<(), ErrorBox> {
+        // the listed entries are being archived or deleted
+        if self.archiver.is_some() || self.batch.is_some() {
+            return Ok(());
+        }
+        self.pattern_error = self.check_pattern().err();
+        if self.pattern_error.is_some() {
+            return Ok(());
+        }
+        self.last_search = Some((self.engine, self.pattern.content.clone()));
+        self.absolute_results = self.engine == MatchEngine::Glob && self.is_absolute_pattern();
+        let (pattern, criteria) = split_criteria(&self.pattern.content)?;
+        // a regex or the text to find can hold `!` and spaces of its own
+        let (pattern, excludes) = match self.engine {
+            MatchEngine::Glob => split_excludes(&pattern),
+            MatchEngine::Regex | MatchEngine::Content => (pattern, vec![]),
+        };
+        self.excludes = compile_excludes(&excludes, self.case_sensitive)?;
+        self.criteria = criteria;
+        let result = match self.engine {
+            MatchEngine::Glob => self.start_glob_search(&pattern),
+            MatchEngine::Regex => self.start_regex_search(&pattern),
+            MatchEngine::Content => self.start_content_search(&pattern),
+        };
 
-        self.list.items[i].toggle_delete();
+        match result {
+            Ok(()) => {}
+            Err(err) if err.is::<RootError>() => {
+                self.status = Some(format!("{}, pick a new one", err));
+                self.open_prompt(PromptKind::Root);
+            }
+            Err(err) => return Err(err),
+        }
+        Ok(())
     }
 
-    pub fn get_entries_by<P>(&self, mut predicate: P) -> Vec<PathEntry>
-    where
-        P: FnMut(&PathEntry) -> bool,
-    {
-        self.list
-            .items
-            .iter()
-            .filter(|e| predicate(e))
-            .cloned()
-            .collect::<Vec<_>>()
-    }
+    /// Parses every word of the pattern before it runs, so a mistake can be
+    /// pointed at where it was typed.
+    fn check_pattern(&self) -> Result<(), PatternError> {
+        let content = &self.pattern.content;
+        let (mut rest, mut rest_start) = (String::new(), None);
+        for word in content.split_whitespace() {
+            let start = word.as_ptr() as usize - content.as_ptr() as usize;
+            let span = start..start + word.len();
+            match Criterion::parse(word) {
+                Some(Err(err)) => return Err(PatternError::new(err.to_string(), span)),
+                Some(Ok(_)) => continue,
+                None => {}
+            }
+            if self.engine == MatchEngine::Glob {
+                if let Some(exclude) = word.strip_prefix('!').filter(|rest| !rest.is_empty()) {
+                    search::check_glob(exclude).map_err(|err| err.shifted(start + 1))?;
+                    continue;
+                }
+                for piece in piece_spans(word) {
+                    search::check_glob(&word[piece.clone()])
+                        .map_err(|err| err.shifted(start + piece.start))?;
+                }
+            }
+            rest_start.get_or_insert(start);
+            if !rest.is_empty() {
+                rest.push(' ');
+            }
+            rest.push_str(word);
+        }
 
-    pub fn delete_active_entries(&mut self) -> Result<(), ErrorBox> {
-        let entries_to_delete = self.get_entries_by(|e| e.is_delete());
-        for entry in entries_to_delete.iter() {
-            if entry.is_file {
-                fs::remove_file(&entry.pathbuf)?
-            } else {
-                fs::remove_dir_all(&entry.pathbuf)?
+        if self.engine == MatchEngine::Regex {
+            if let Err(err) = RegexBuilder::new(&rest).build() {
+                return Err(search::regex_error(&rest, &err).shifted(rest_start.unwrap_or(0)));
             }
         }
+        Ok(())
+    }
 
-        let entries = self.get_entries_by(|e| !e.is_delete());
-        self.update_list(entries);
+    /// Runs the pattern and adds it to the history.
+    pub fn submit_pattern(&mut self) -> Result<(), ErrorBox> {
+        self.history.push(&self.pattern.content);
+        if let Err(err) = self.history.save() {
+            self.status = Some(format!("could not save the history: {}", err));
+        }
+        self.set_pattern()
+    }
 
-        Ok(())
+    /// Replaces the pattern with the previous one from the history.
+    pub fn history_previous(&mut self) {
+        if let Some(pattern) = self.history.older(&self.pattern.content) {
+            self.pattern.content = pattern.to_string();
+        }
     }
-}
-#[derive(Clone)]
-pub struct PathEntry {
-    pub pathbuf: PathBuf,
-    pub is_file: bool,
-    _is_delete: bool,
-}
 
-impl PathEntry {
-    pub fn new(pathbuf: PathBuf) -> Self {
-        PathEntry {
-            is_file: pathbuf.is_file(),
-            pathbuf,
-            _is_delete: true,
+    /// Replaces the pattern with the next one from the history.
+    pub fn history_next(&mut self) {
+        if let Some(pattern) = self.history.newer() {
+            self.pattern.content = pattern.to_string();
         }
     }
 
-    pub fn toggle_delete(&mut self) {
-        self._is_delete = !self._is_delete;
+    /// Runs the pattern while it's being typed. Errors, like those of a half
+    /// typed pattern, go to the status line instead.
+    pub fn search_live(&mut self) {
+        if self.pattern.content.is_empty() {
+            return;
+        }
+        if let Err(err) = self.set_pattern() {
+            self.status = Some(err.to_string());
+        }
     }
 
-    pub fn is_delete(&self) -> bool {
-        self._is_delete
+    pub fn open_prompt(&mut self, kind: PromptKind) {
+        self.prompt = Some(Prompt::new(kind));
+        self.set_app_mode(AppMode::Prompt);
     }
-}
 
-pub struct StatefulList<T> {
-    pub state: ListState,
-    pub items: Vec<T>,
-}
+    pub fn close_prompt(&mut self) {
+        self.prompt = None;
+        self.set_app_mode(AppMode::Normal);
+    }
 
-impl<T> StatefulList<T> {
-    pub fn new() -> Self {
-        StatefulList {
-            state: ListState::default(),
-            items: vec![],
+    pub fn submit_prompt(&mut self) -> Result<(), ErrorBox> {
+        let prompt = match self.prompt.take() {
+            Some(prompt) => prompt,
+            None => return Ok(()),
+        };
+        self.set_app_mode(AppMode::Normal);
+
+        match prompt.kind {
+            PromptKind::Root => self.set_root(PathBuf::from(&prompt.input.content)),
+            PromptKind::ComparePattern => self.compare_pattern(&prompt.input.content),
+            PromptKind::TimeRange => {
+                match TimeRange::parse(&prompt.input.content) {
+                    Ok(range) => self.apply_time_range(&range),
+                    Err(err) => {
+                        self.status = Some(err.to_string());
+                        self.prompt = Some(prompt);
+                        self.set_app_mode(AppMode::Prompt);
+                    }
+                }
+                Ok(())
+            }
+            PromptKind::Filter => {
+                self.set_filter(&prompt.input.content);
+                Ok(())
+            }
+            PromptKind::Command => {
+                self.run_command(&prompt.input.content);
+                Ok(())
+            }
+            PromptKind::Export => {
+                self.export_to(&prompt.input.content, false);
+                Ok(())
+            }
+            PromptKind::ExportMarked => {
+                self.export_to(&prompt.input.content, true);
+                Ok(())
+            }
+            PromptKind::PresetName => {
+                if let Err(err) = self.save_current_as_preset(&prompt.input.content) {
+                    self.status = Some(err.to_string());
+                    self.prompt = Some(prompt);
+                    self.set_app_mode(AppMode::Prompt);
+                }
+                Ok(())
+            }
         }
     }
 
-    pub fn with_items(items: Vec<T>) -> StatefulList<T> {
-        let mut stateful_list = StatefulList {
-            state: ListState::default(),
-            items,
+    /// Parses and runs a line typed on the `:` command line. Mistakes go to
+    /// the status line.
+    pub fn run_command_line(&mut self, line: &str) -> Result<(), ErrorBox> {
+        let command = match ExCommand::parse(line) {
+            Ok(Some(command)) => command,
+            Ok(None) => return Ok(()),
+            Err(err) => {
+                self.status = Some(err.to_string());
+                return Ok(());
+            }
         };
-        stateful_list.state.select(Some(0));
-        stateful_list
+
+        match command {
+            ExCommand::Sort { key, descending } => {
+                self.sort = key;
+                self.sort_descending = descending;
+                self.sort_entries();
+            }
+            ExCommand::Filter(query) => self.set_filter(&query),
+            ExCommand::Delete { force } => {
+                self.request_delete(self.permanent_delete());
+                if force && matches!(self.confirm, Some(ConfirmAction::Delete { .. })) {
+                    self.accept_confirm()?;
+                }
+            }
+            ExCommand::Cd(dir) => match dir.or_else(dirs::home_dir) {
+                Some(dir) => self.set_root(dir)?,
+                None => self.status = Some(String::from("no home directory to go to")),
+            },
+            ExCommand::Write(path) => self.export_to(&path.to_string_lossy(), false),
+            ExCommand::Quit { force: true } => self.should_quit = true,
+            ExCommand::Quit { force: false } => self.request_quit(),
+        }
+        Ok(())
     }
 
-    pub fn get_index(&self) -> Option<usize> {
-        self.state.selected()
+    /// Enters visual mode, anchored at the selected entry.
+    pub fn start_visual(&mut self) {
+        let anchor = match self.selected_path() {
+            Some(path) => path.to_path_buf(),
+            None => return,
+        };
+        self.visual = Some(Visual {
+            anchor,
+            before: HashMap::new(),
+        });
+        self.set_app_mode(AppMode::Visual);
+        self.update_visual();
     }
 
-    pub fn next(&mut self) {
-        if self.items.is_empty() {
-            return;
-        }
+    /// The indexes from the visual mode anchor to the selection, either way.
+    pub fn visual_range(&self) -> Option<Range<usize>> {
+        let visual = self.visual.as_ref()?;
+        let anchor = self
+            .list
+            .items
+            .iter()
+            .position(|e| e.pathbuf == visual.anchor)?;
+        let selected = self.list.get_index()?;
+        Some(anchor.min(selected)..anchor.max(selected) + 1)
+    }
 
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.items.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
+    /// Marks the entries in the visual range, and puts back how the ones it
+    /// left were marked. Leaves visual mode once the anchor is gone, like
+    /// when a search replaced the results.
+    fn update_visual(&mut self) {
+        let range = match self.visual_range() {
+            Some(range) => range,
+            None => {
+                self.end_visual(true);
+                return;
             }
-            None => 0,
         };
-        self.state.select(Some(i));
+        let visual = match self.visual.as_mut() {
+            Some(visual) => visual,
+            None => return,
+        };
+        let protected = &self.config.protected_paths;
+        for (i, entry) in self.list.items.iter_mut().enumerate() {
+            if range.contains(&i) {
+                // headings are never deleted, protected entries can't be
+                if entry.group || protect::protected_reason(&entry.pathbuf, protected).is_some() {
+                    continue;
+                }
+                visual
+                    .before
+                    .entry(entry.pathbuf.clone())
+                    .or_insert(entry.is_delete());
+                entry.set_delete(true);
+            } else if let Some(marked) = visual.before.remove(&entry.pathbuf) {
+                entry.set_delete(marked);
+            }
+        }
     }
 
-    pub fn previous(&mut self) {
-        if self.items.is_empty() {
+    /// Leaves visual mode, keeping the marks of the range or putting back
+    /// how the entries were marked before.
+    pub fn end_visual(&mut self, keep: bool) {
+        self.set_app_mode(AppMode::Normal);
+        let range = self.visual_range();
+        let visual = match self.visual.take() {
+            Some(visual) => visual,
+            None => return,
+        };
+        if keep {
+            if let Some(range) = range {
+                self.remember_marks(range);
+            }
+            let changes = self
+                .list
+                .items
+                .iter()
+                .filter_map(|entry| {
+                    let marked = *visual.before.get(&entry.pathbuf)?;
+                    (marked != entry.is_delete()).then(|| (entry.pathbuf.clone(), marked))
+                })
+                .collect();
+            self.push_marks(changes);
             return;
         }
-
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.items.len() - 1
-                } else {
-                    i - 1
-                }
+        for entry in self.list.items.iter_mut() {
+            if let Some(marked) = visual.before.get(&entry.pathbuf) {
+                entry.set_delete(*marked);
             }
-            None => 0,
+        }
+    }
+
+    /// Marks `count` entries from the selected one, then asks to delete
+    /// them, like `dd` in vi. Other marked entries are left alone.
+    pub fn mark_and_delete(&mut self, count: usize) {
+        // the first d may have found nothing marked
+        self.status = None;
+        let start = match self.list.get_index() {
+            Some(start) => start,
+            None => return self.request_delete(self.permanent_delete()),
         };
-        self.state.select(Some(i));
+        let end = start.saturating_add(count).min(self.list.items.len());
+        let before = self.mark_states(start..end);
+        for entry in self.list.items[start..end].iter_mut() {
+            entry.set_delete(true);
+        }
+        let protected = self.unmark_protected();
+        self.remember_marks(start..end);
+        self.record_marks(start..end, before);
+        if protected {
+            return;
+        }
+        let only = self.list.items[start..end]
+            .iter()
+            .filter(|e| e.is_delete())
+            .map(|e| e.pathbuf.clone())
+            .collect();
+        self.confirm_delete(self.permanent_delete(), Some(only));
     }
 
-    pub fn unselect(&mut self) {
-        self.state.select(None);
+    /// Asks for confirmation before deleting the marked entries, to the
+    /// trash unless `permanent`.
+    pub fn request_delete(&mut self, permanent: bool) {
+        // hidden entries are deleted too, they should be in sight
+        self.expand_all();
+        self.confirm_delete(permanent, None);
+    }
+
+    /// Asks to delete the marked entries, or `only` those of them.
+    fn confirm_delete(&mut self, permanent: bool, only: Option<HashSet<PathBuf>>) {
+        let marked = self.marked_entries(only.as_ref());
+        if marked.is_empty() {
+            self.status = Some(String::from("nothing is marked for deletion"));
+            return;
+        }
+        self.confirm = Some(ConfirmAction::Delete {
+            permanent,
+            count: marked.len(),
+            only,
+        });
+        self.set_app_mode(AppMode::Confirm);
+    }
+
+    /// Stops the search and the deletion for a clean exit, after the entries
+    /// being deleted are done, and quits. What the deletion got through is
+    /// left in `exit_report`.
+    pub fn interrupt(&mut self) {
+        self.stop_search();
+        self.should_quit = true;
+        let mut batch = match self.batch.take() {
+            Some(batch) => batch,
+            None => return,
+        };
+        if let Some(deletion) = batch.deletion.take() {
+            deletion.cancel();
+            for message in deletion.wait() {
+                batch.apply(message);
+            }
+        }
+
+        self.exit_report = batch.report();
+        self.exit_report
+            .insert(0, String::from("Interrupted while deleting"));
+        batch.cancelled = true;
+        batch.pending.clear();
+        batch.current = None;
+        self.confirm = None;
+        self.finish_delete(batch);
+    }
+
+    /// Quits right away or asks first, depending on `confirm_on_quit`.
+    pub fn request_quit(&mut self) {
+        let ask = match self.config.confirm_on_quit {
+            QuitConfirm::Always => true,
+            QuitConfirm::WhenMarked => self.list.items.iter().any(|e| e.is_delete()),
+            QuitConfirm::Never => false,
+        };
+        if ask {
+            self.confirm = Some(ConfirmAction::Quit);
+            self.set_app_mode(AppMode::Confirm);
+        } else {
+            self.should_quit = true;
+        }
+    }
+
+    pub fn accept_confirm(&mut self) -> Result<(), ErrorBox> {
+        self.set_app_mode(AppMode::Normal);
+        match self.confirm.take() {
+            Some(ConfirmAction::Delete {
+                permanent, only, ..
+            }) => self.delete_marked(permanent, only.as_ref()),
+            Some(ConfirmAction::DeleteDir { .. }) => {
+                let approved = self.batch.as_mut().and_then(|b| b.pending.pop_front());
+                self.resume_delete(approved);
+                Ok(())
+            }
+            Some(ConfirmAction::Quit) => {
+                self.should_quit = true;
+                Ok(())
+            }
+            Some(ConfirmAction::OverwritePreset(preset)) => {
+                self.store_preset(preset);
+                Ok(())
+            }
+            Some(ConfirmAction::Archive { .. }) => self.archive_marked(),
+            Some(ConfirmAction::PurgeQuarantine(batch)) => {
+                self.status = Some(match quarantine::purge(&batch) {
+                    Ok(()) => format!("purged {} entries", batch.manifest.entries.len()),
+                    Err(err) => format!("could not purge {}: {}", batch.dir.display(), err),
+                });
+                self.open_quarantine();
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn cancel_confirm(&mut self) -> Result<(), ErrorBox> {
+        self.set_app_mode(AppMode::Normal);
+        match self.confirm.take() {
+            Some(ConfirmAction::PurgeQuarantine(_)) => {
+                self.set_app_mode(AppMode::Quarantine);
+                Ok(())
+            }
+            // skip this directory, the rest of the batch goes on
+            Some(ConfirmAction::DeleteDir { .. }) => {
+                if let Some(batch) = self.batch.as_mut() {
+                    batch.pending.pop_front();
+                }
+                self.resume_delete(None);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Lists the quarantined batches to restore or purge, newest first.
+    pub fn open_quarantine(&mut self) {
+        let batches = quarantine::dir()
+            .map(|dir| quarantine::list(&dir))
+            .unwrap_or_default();
+        if batches.is_empty() {
+            self.set_app_mode(AppMode::Normal);
+            self.status = Some(String::from("the quarantine is empty"));
+            return;
+        }
+        self.quarantine = StatefulList::with_items(batches);
+        self.set_app_mode(AppMode::Quarantine);
+    }
+
+    /// Moves the entries of the selected batch back where they were, then
+    /// runs the pattern again to list them.
+    pub fn restore_selected_batch(&mut self) -> Result<(), ErrorBox> {
+        let batch = match self.quarantine.get_index() {
+            Some(i) => self.quarantine.items[i].clone(),
+            None => return Ok(()),
+        };
+        let status = match quarantine::restore(&batch) {
+            Ok(restored) => format!("restored {} entries", restored),
+            Err(err) => format!("could not restore everything: {}", err),
+        };
+        self.open_quarantine();
+        if !self.pattern.content.is_empty() {
+            self.refresh()?;
+        }
+        self.status = Some(status);
+        Ok(())
+    }
+
+    /// Asks before purging the selected batch.
+    pub fn request_purge(&mut self) {
+        if let Some(i) = self.quarantine.get_index() {
+            self.confirm = Some(ConfirmAction::PurgeQuarantine(
+                self.quarantine.items[i].clone(),
+            ));
+            self.set_app_mode(AppMode::Confirm);
+        }
+    }
+
+    pub fn open_presets(&mut self) {
+        self.presets = StatefulList::with_items(presets::all(&self.config));
+        self.set_app_mode(AppMode::Presets);
+    }
+
+    /// Searches with the selected preset, leaving every match marked for review.
+    pub fn apply_selected_preset(&mut self) -> Result<(), ErrorBox> {
+        self.set_app_mode(AppMode::Normal);
+        match self.presets.get_index() {
+            Some(i) => self.apply_preset(self.presets.items[i].clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Searches with the pattern, engine and case sensitivity of `preset`.
+    pub fn apply_preset(&mut self, preset: Preset) -> Result<(), ErrorBox> {
+        self.pattern.content = preset.search_pattern();
+        self.engine = preset.engine;
+        if let Some(case_sensitive) = preset.case_sensitive {
+            self.case_sensitive = case_sensitive;
+        }
+        self.set_pattern()?;
+        if self.prompt.is_some() {
+            return Ok(());
+        }
+
+        // matches stream in marked, ready for review
+        self.status = Some(format!("{}: review the matches", preset.name));
+        Ok(())
+    }
+
+    /// Saves the pattern, engine and case sensitivity as a config preset.
+    /// Replacing a preset of the same name is confirmed first.
+    pub fn save_current_as_preset(&mut self, name: &str) -> Result<(), ErrorBox> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("the preset needs a name".into());
+        }
+        if self.pattern.content.is_empty() {
+            return Err("nothing to save, the pattern is empty".into());
+        }
+        if presets::builtin().iter().any(|preset| preset.name == name) {
+            return Err(format!("'{}' is a built-in preset", name).into());
+        }
+
+        let preset = Preset {
+            name: name.to_string(),
+            pattern: self.pattern.content.clone(),
+            engine: self.engine,
+            case_sensitive: Some(self.case_sensitive),
+            ..Preset::new(name, &self.pattern.content)
+        };
+        if self.config.presets.iter().any(|other| other.name == name) {
+            self.confirm = Some(ConfirmAction::OverwritePreset(preset));
+            self.set_app_mode(AppMode::Confirm);
+            return Ok(());
+        }
+        self.store_preset(preset);
+        Ok(())
+    }
+
+    fn store_preset(&mut self, preset: Preset) {
+        self.status = Some(format!("saved the preset '{}'", preset.name));
+        match self
+            .config
+            .presets
+            .iter_mut()
+            .find(|p| p.name == preset.name)
+        {
+            Some(existing) => *existing = preset,
+            None => self.config.presets.push(preset),
+        }
+        self.save_config();
+    }
+
+    /// Changes the directory relative patterns are resolved against and
+    /// re-runs the current pattern, if any.
+    pub fn set_root(&mut self, root: PathBuf) -> Result<(), ErrorBox> {
+        let root = self.root.join(root);
+        if !root.is_dir() {
+            self.status = Some(format!("not a directory: {}", root.display()));
+            self.open_prompt(PromptKind::Root);
+            return Ok(());
+        }
+
+        self.root = root.canonicalize()?;
+        self.status = Some(format!("search root: {}", self.root.display()));
+        self.update_watcher();
+        if !self.pattern.content.is_empty() {
+            self.set_pattern()?;
+        }
+        Ok(())
+    }
+
+    /// Whether the entry lives on another filesystem than the search root,
+    /// e.g. below a mount point.
+    pub fn is_cross_mount(&self, entry: &PathEntry) -> bool {
+        entry.filesystem.is_some() && entry.filesystem != filesystem_id(&self.root)
+    }
+
+    /// The entry path relative to the search root, or in full when it's
+    /// outside of it or the results come from an absolute pattern.
+    pub fn display_path<'a>(&self, entry: &'a PathEntry) -> &'a Path {
+        if self.absolute_results {
+            return &entry.pathbuf;
+        }
+        entry
+            .pathbuf
+            .strip_prefix(&self.root)
+            .unwrap_or(&entry.pathbuf)
+    }
+
+    /// Byte ranges of the path column text of `entry` that the pattern
+    /// matched, see `Highlighter::spans`. Empty when nothing in view was.
+    pub fn match_spans(&self, entry: &PathEntry) -> Vec<Range<usize>> {
+        let highlighter = match &self.highlighter {
+            Some(highlighter) => highlighter,
+            None => return vec![],
+        };
+        let path = entry.pathbuf.to_string_lossy();
+        let text = self.column_text(entry, Column::Path);
+        // the text ends with the part of the path in view
+        let shown = match self.tree_view {
+            true => entry
+                .pathbuf
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy(),
+            false => self.display_path(entry).to_string_lossy(),
+        };
+        if !path.ends_with(&*shown) || !text.ends_with(&*shown) {
+            return vec![];
+        }
+        let (from, to) = (path.len() - shown.len(), text.len() - shown.len());
+        highlighter
+            .spans(&path)
+            .into_iter()
+            .filter(|span| span.end > from)
+            .map(|span| span.start.max(from) - from + to..span.end - from + to)
+            .collect()
+    }
+
+    /// What `entry` shows in `column` of the result list.
+    pub fn column_text(&self, entry: &PathEntry, column: Column) -> String {
+        match column {
+            Column::Type => String::from(match entry.special {
+                Some(kind) => kind,
+                None if entry.is_dir => "Dir",
+                None => "File",
+            }),
+            Column::Size => match entry.special {
+                Some(_) => String::from("n/a"),
+                None => self
+                    .entry_size(entry)
+                    .map_or(String::from("-"), humanize::size),
+            },
+            Column::Mtime => entry.modified.map_or(String::from("?"), humanize::age),
+            Column::Permissions => entry
+                .permissions
+                .as_ref()
+                .map_or(String::from("?"), humanize::permissions),
+            Column::Path => match self.tree_view {
+                true => format!(
+                    "{}{}",
+                    "  ".repeat(self.tree_depth(entry)),
+                    entry
+                        .pathbuf
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                ),
+                false => self.display_path(entry).display().to_string(),
+            },
+        }
+    }
+
+    /// Whether any of the glob patterns is absolute.
+    pub fn is_absolute_pattern(&self) -> bool {
+        split_patterns(&split_excludes(&self.pattern.content).0)
+            .iter()
+            .any(|pattern| Path::new(pattern).is_absolute())
+    }
+
+    /// Whether a search result is kept, not excluded. The search only sends
+    /// what meets every criterion.
+    fn is_wanted(&self, entry: &PathEntry) -> bool {
+        !self.is_excluded(entry, &self.excludes)
+    }
+
+    /// Whether the entry, or a directory it's in, matches one of the
+    /// exclude patterns. Paths below the root are matched relative to it.
+    fn is_excluded(&self, entry: &PathEntry, excludes: &GlobSet) -> bool {
+        let path = entry
+            .pathbuf
+            .strip_prefix(&self.root)
+            .unwrap_or(&entry.pathbuf);
+        path.ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .any(|ancestor| excludes.is_match(ancestor))
+    }
+
+    pub fn is_case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    pub fn toggle_case_sensitive(&mut self) {
+        self.case_sensitive = !self.case_sensitive;
+    }
+
+    /// Puts every search option back to its default: the case sensitivity,
+    /// the engine, skipping ignored entries, the time range, the filter and
+    /// the mark view. The exclude and criteria words go from the pattern,
+    /// which runs again so the results match what it shows.
+    pub fn reset_search_options(&mut self) -> Result<(), ErrorBox> {
+        let pattern = split_criteria(&self.pattern.content)
+            .map(|(pattern, _)| pattern)
+            .unwrap_or_else(|_| self.pattern.content.clone());
+        // `!` words are excludes again once the engine is back to globs
+        self.pattern.content = split_excludes(&pattern).0;
+        self.case_sensitive = self.config.case_sensitive;
+        self.engine = MatchEngine::Glob;
+        self.ignore_toggled = false;
+        self.time_range = None;
+        self.mark_view = MarkView::All;
+        self.set_filter("");
+        if !self.pattern.content.is_empty() {
+            self.set_pattern()?;
+        }
+        self.status = Some(String::from("search options reset to defaults"));
+        Ok(())
+    }
+
+    fn check_root(&self) -> Result<(), ErrorBox> {
+        if fs::read_dir(&self.root).is_err() {
+            return Err(RootError(self.root.clone()).into());
+        }
+        Ok(())
+    }
+
+    /// `pattern` joined onto the search root, or on its own when it's an
+    /// absolute pattern, in which case the root doesn't matter.
+    fn glob_pattern(&self, pattern: &str) -> Result<String, ErrorBox> {
+        if Path::new(pattern).is_absolute() {
+            return Ok(pattern.to_string());
+        }
+        self.check_root()?;
+        let root = globset::escape(&self.root.to_string_lossy());
+        Ok(Path::new(&root)
+            .join(pattern)
+            .to_string_lossy()
+            .into_owned())
+    }
+
+    /// Globs `pattern` like `set_pattern` does, on this thread.
+    fn search(&self, pattern: &str) -> Result<Vec<PathEntry>, ErrorBox> {
+        let (pattern, criteria) = split_criteria(pattern)?;
+        let (pattern, excludes) = split_excludes(&pattern);
+        let excludes = compile_excludes(&excludes, self.case_sensitive)?;
+        let globs = self.compile_globs(&pattern)?;
+        let entries = globs
+            .paths(self.respect_ignore())
+            .map(PathEntry::new)
+            .filter(|entry| !self.is_excluded(entry, &excludes))
+            .filter(|entry| criteria.iter().all(|c| c.matches(entry)))
+            .collect();
+        Ok(entries)
+    }
+
+    /// The patterns of `pattern` joined onto the root.
+    fn glob_patterns(&self, pattern: &str) -> Result<Vec<String>, ErrorBox> {
+        split_patterns(pattern)
+            .iter()
+            .map(|pattern| self.glob_pattern(pattern))
+            .collect()
+    }
+
+    /// The patterns of `pattern` joined onto the root and compiled.
+    fn compile_globs(&self, pattern: &str) -> Result<Globs, ErrorBox> {
+        Globs::new(&self.glob_patterns(pattern)?, self.case_sensitive)
+    }
+
+    fn start_glob_search(&mut self, pattern: &str) -> Result<(), ErrorBox> {
+        let patterns = self.glob_patterns(pattern)?;
+        let globs = Globs::new(&patterns, self.case_sensitive)?;
+        self.start_search(Search::glob(
+            globs,
+            self.criteria.clone(),
+            self.respect_ignore(),
+            self.config.search_threads,
+        ));
+        self.highlighter = Some(Highlighter::glob(&patterns, self.case_sensitive));
+        Ok(())
+    }
+
+    fn start_content_search(&mut self, pattern: &str) -> Result<(), ErrorBox> {
+        self.check_root()?;
+        self.start_search(Search::content(
+            self.root.clone(),
+            pattern.to_string(),
+            self.case_sensitive,
+            self.criteria.clone(),
+            self.respect_ignore(),
+            self.config.search_threads,
+        ));
+        Ok(())
+    }
+
+    fn start_regex_search(&mut self, pattern: &str) -> Result<(), ErrorBox> {
+        let regex = match RegexBuilder::new(pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+        {
+            Ok(regex) => regex,
+            Err(err) => {
+                self.status = Some(format!("invalid regex: {}", err));
+                return Ok(());
+            }
+        };
+
+        self.check_root()?;
+        self.start_search(Search::regex(
+            self.root.clone(),
+            regex.clone(),
+            self.criteria.clone(),
+            self.respect_ignore(),
+            self.config.search_threads,
+        ));
+        self.highlighter = Some(Highlighter::Regex(regex));
+        Ok(())
+    }
+
+    /// Replaces the listed entries with the results of `search` as they come.
+    fn start_search(&mut self, search: Search) {
+        self.clear_results();
+        self.search = Some(search);
+        self.searching = true;
+    }
+
+    /// Drops the results and everything derived from them.
+    fn clear_results(&mut self) {
+        self.stop_search();
+        self.list = StatefulList::new();
+        self.filter.clear();
+        self.mark_view = MarkView::All;
+        self.filtered_out.clear();
+        self.collapsed.clear();
+        self.overflow.clear();
+        self.result_limit = self.config.max_results;
+        self.duplicate_finder = None;
+        self.duplicate_groups.clear();
+        self.time_range = None;
+        self.mark_undo.clear();
+        self.mark_redo.clear();
+        self.highlighter = None;
+    }
+
+    /// Lists `paths` as if a search had found them, instead of running the
+    /// pattern. Relative paths are taken from the current directory, like a
+    /// tool piping them in would, and paths that don't exist are left out.
+    pub fn load_paths(&mut self, paths: Vec<PathBuf>) {
+        self.clear_results();
+        self.pattern.content.clear();
+        let cwd = env::current_dir().unwrap_or_default();
+        let total = paths.len();
+        for path in paths {
+            let path = cwd.join(path);
+            if path.symlink_metadata().is_ok() {
+                self.push_entry(PathEntry::new(path));
+            }
+        }
+        self.finish_search();
+
+        let loaded = self.list.items.len() + self.overflow.len();
+        self.status = Some(match total - loaded {
+            0 => format!("loaded {} paths", loaded),
+            missing => format!("loaded {} paths, {} don't exist", loaded, missing),
+        });
+    }
+
+    /// The listed entries, or only the marked ones, as they're exported.
+    pub fn exported_entries(&self, marked_only: bool) -> Vec<ExportedEntry> {
+        self.list
+            .items
+            .iter()
+            .filter(|e| !e.group && (!marked_only || e.is_delete()))
+            .map(|e| ExportedEntry::new(e, self.entry_size(e)))
+            .collect()
+    }
+
+    /// Writes the listed entries, or only the marked ones, to `path`, as
+    /// JSON when it ends with `.json`.
+    pub fn export_to(&mut self, path: &str, marked_only: bool) {
+        let path = PathBuf::from(path.trim());
+        if path.as_os_str().is_empty() {
+            return;
+        }
+        let entries = self.exported_entries(marked_only);
+        let written = export::render(&entries, export::Format::from_path(&path))
+            .and_then(|text| Ok(fs::write(&path, text)?));
+        self.status = Some(match written {
+            Ok(()) => format!("exported {} entries to {}", entries.len(), path.display()),
+            Err(err) => format!("could not export to {}: {}", path.display(), err),
+        });
+    }
+
+    /// Hides the listed entries whose path doesn't fuzzy match `query`, or
+    /// match it as a glob when it has wildcards like `*.log`, and those left
+    /// out by `mark_view`. An empty query shows everything again. Runs on the
+    /// loaded entries, the search isn't repeated.
+    pub fn set_filter(&mut self, query: &str) {
+        let glob = match query.contains(['*', '?', '[', '{']) {
+            true => match GlobBuilder::new(query).case_insensitive(true).build() {
+                Ok(glob) => Some(glob.compile_matcher()),
+                Err(err) => {
+                    self.status = Some(format!("invalid filter: {}", err));
+                    return;
+                }
+            },
+            false => None,
+        };
+        let selected = self.selected_path().map(Path::to_path_buf);
+
+        // put the hidden entries back where they were
+        for (i, entry) in self.filtered_out.drain(..) {
+            let i = i.min(self.list.items.len());
+            self.list.items.insert(i, entry);
+        }
+
+        self.filter = query.to_string();
+        if !query.is_empty() || self.mark_view != MarkView::All {
+            let items = std::mem::take(&mut self.list.items);
+            for (i, entry) in items.into_iter().enumerate() {
+                let path = self.display_path(&entry).to_string_lossy();
+                let matches = match &glob {
+                    Some(glob) => glob.is_match(path.as_ref()),
+                    None => fuzzy_match(query, &path),
+                };
+                // headings stay for the entries inside them
+                if matches && (entry.group || self.mark_view.shows(&entry)) {
+                    self.list.items.push(entry);
+                } else {
+                    self.filtered_out.push((i, entry));
+                }
+            }
+        }
+
+        if self.tree_view {
+            self.build_tree();
+        }
+        let found = selected.is_some_and(|path| self.select_by_path(&path));
+        if !found {
+            self.list.select_index(0);
+        }
+    }
+
+    /// Shows the marked entries only, then the unmarked ones, then all of
+    /// them again. Entries marked or unmarked meanwhile stay in view until
+    /// the next time.
+    pub fn cycle_mark_view(&mut self) {
+        self.mark_view = self.mark_view.cycle();
+        let query = self.filter.clone();
+        self.set_filter(&query);
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.searching
+    }
+
+    /// Whether work goes on in the background, changing what's shown as
+    /// it goes.
+    pub fn is_busy(&self) -> bool {
+        self.searching
+            || self.is_deleting()
+            || !self.pending_sizes.is_empty()
+            || self.duplicate_finder.is_some()
+            || self.command.is_some()
+            || self.archiver.is_some()
+    }
+
+    /// Picks up whatever the background workers sent since the last call.
+    pub fn poll_workers(&mut self) -> Result<(), ErrorBox> {
+        self.poll_search();
+        self.poll_delete();
+        self.poll_sizes();
+        self.poll_duplicates();
+        self.poll_command();
+        self.poll_archive()?;
+        self.poll_watcher()
+    }
+
+    /// Moves results from the running search into the list.
+    pub fn poll_search(&mut self) {
+        if let Some(search) = &self.search {
+            let messages = search.poll();
+            self.handle_search_messages(messages);
+        }
+    }
+
+    /// Blocks until the running search is done, for use without an event loop.
+    pub fn wait_for_search(&mut self) {
+        if let Some(search) = &self.search {
+            let messages = search.wait();
+            self.handle_search_messages(messages);
+        }
+    }
+
+    fn handle_search_messages(&mut self, messages: Vec<SearchMessage>) {
+        for message in messages {
+            match message {
+                SearchMessage::Found(entry) if !self.is_wanted(&entry) => {}
+                SearchMessage::Found(entry) => self.push_result(entry),
+                SearchMessage::Done => {
+                    self.search = None;
+                    self.finish_search();
+                }
+            }
+        }
+    }
+
+    /// Stops the running search, keeping what it found so far.
+    pub fn cancel_search(&mut self) {
+        if self.stop_search() {
+            self.status = Some(format!(
+                "search cancelled, {} results so far",
+                self.list.items.len()
+            ));
+        }
+    }
+
+    /// Like `cancel_search`, quietly. Returns whether a search was running.
+    fn stop_search(&mut self) -> bool {
+        match self.search.take() {
+            Some(search) => {
+                search.cancel();
+                self.finish_search();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Takes effect on the next search.
+    pub fn toggle_respect_ignore(&mut self) {
+        self.ignore_toggled = !self.ignore_toggled;
+        self.status = Some(String::from(match self.respect_ignore() {
+            true => "skipping ignored entries from the next search on",
+            false => "including ignored entries from the next search on",
+        }));
+    }
+
+    pub fn toggle_watch(&mut self) {
+        self.watch_toggled = !self.watch_toggled;
+        self.update_watcher();
+    }
+
+    /// Starts or stops watching the search root to match `watch_root`.
+    pub fn update_watcher(&mut self) {
+        self.watcher = None;
+        if !self.watch_root() {
+            return;
+        }
+        match RootWatcher::new(&self.root) {
+            Ok(watcher) => self.watcher = Some(watcher),
+            Err(err) => {
+                self.watch_toggled = self.config.watch;
+                self.status = Some(format!("could not watch {}: {}", self.root.display(), err));
+            }
+        }
+    }
+
+    /// Runs the pattern again to list what matches now, keeping the
+    /// selection on the same path when it's still there.
+    pub fn refresh(&mut self) -> Result<(), ErrorBox> {
+        if self.pattern.content.is_empty() {
+            self.status = Some(String::from("no pattern to refresh"));
+            return Ok(());
+        }
+        self.reselect = self.selected_path().map(Path::to_path_buf);
+        self.set_pattern()
+    }
+
+    /// Re-runs the pattern when the watcher saw relevant changes on disk.
+    pub fn poll_watcher(&mut self) -> Result<(), ErrorBox> {
+        let due = match self.watcher.as_mut() {
+            Some(watcher) => watcher.refresh_due(),
+            None => return Ok(()),
+        };
+        let busy = self.is_searching() || self.archiver.is_some() || self.batch.is_some();
+        if due && !self.pattern.content.is_empty() && !busy {
+            self.set_pattern()?;
+            self.status = Some(String::from("refreshed after changes on disk"));
+        }
+        Ok(())
+    }
+
+    pub fn cycle_engine(&mut self) {
+        self.engine = self.engine.next();
+    }
+
+    pub fn on_tick(&mut self) {
+        self.spinner = self.spinner.wrapping_add(1);
+    }
+
+    /// Runs both patterns and splits their matches by which one found them.
+    pub fn diff_patterns(&self, a: &str, b: &str) -> Result<PatternDiff, ErrorBox> {
+        let paths = |pattern| -> Result<BTreeSet<PathBuf>, ErrorBox> {
+            Ok(self
+                .search(pattern)?
+                .into_iter()
+                .map(|entry| entry.pathbuf)
+                .collect())
+        };
+        let (a_paths, b_paths) = (paths(a)?, paths(b)?);
+
+        Ok(PatternDiff {
+            pattern_a: a.to_string(),
+            pattern_b: b.to_string(),
+            only_a: a_paths.difference(&b_paths).cloned().collect(),
+            only_b: b_paths.difference(&a_paths).cloned().collect(),
+            both: a_paths.intersection(&b_paths).cloned().collect(),
+        })
+    }
+
+    pub fn compare_pattern(&mut self, other: &str) -> Result<(), ErrorBox> {
+        let diff = self.diff_patterns(&self.pattern.content, other)?;
+        self.pattern_diff = Some(diff);
+        Ok(())
+    }
+
+    /// What the directories holding marked entries would contain once they
+    /// are deleted. Directories going away themselves are left out.
+    pub fn dry_run_tree_diff(&self) -> Vec<DirDiff> {
+        let marked: BTreeSet<&Path> = self
+            .list
+            .items
+            .iter()
+            .filter(|e| e.is_delete())
+            .map(|e| e.pathbuf.as_path())
+            .collect();
+        let dirs: BTreeSet<&Path> = marked
+            .iter()
+            .filter_map(|path| path.parent())
+            .filter(|dir| !marked.iter().any(|m| dir.starts_with(m)))
+            .collect();
+
+        dirs.into_iter()
+            .filter_map(|dir| {
+                let mut children: Vec<PathBuf> = fs::read_dir(dir)
+                    .ok()?
+                    .filter_map(Result::ok)
+                    .map(|child| child.path())
+                    .collect();
+                children.sort();
+
+                let name = |path: &PathBuf| {
+                    path.file_name()
+                        .map_or(String::new(), |name| name.to_string_lossy().into_owned())
+                };
+                Some(DirDiff {
+                    dir: dir.to_path_buf(),
+                    before: children.iter().map(name).collect(),
+                    after: children
+                        .iter()
+                        .filter(|child| !marked.contains(child.as_path()))
+                        .map(name)
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn show_tree_diff(&mut self) {
+        let diff = self.dry_run_tree_diff();
+        if diff.is_empty() {
+            self.status = Some(String::from("nothing is marked for deletion"));
+            return;
+        }
+        self.tree_diff = Some(diff);
+    }
+
+    /// Lists a search match, or keeps its path for later once the result
+    /// cap is reached.
+    fn push_result(&mut self, entry: PathEntry) {
+        if self.config.max_results > 0 && self.listed_count() >= self.result_limit {
+            self.overflow.push_back(entry.pathbuf);
+        } else {
+            self.push_entry(entry);
+        }
+    }
+
+    /// Entries listed, hidden by the filter or not.
+    pub fn listed_count(&self) -> usize {
+        self.list.items.len() + self.filtered_out.len()
+    }
+
+    /// Lists the next batch of matches past the result cap.
+    pub fn load_more_results(&mut self) {
+        if self.overflow.is_empty() {
+            self.status = Some(String::from("every match is listed"));
+            return;
+        }
+        self.result_limit += self.config.max_results;
+        let batch = self.config.max_results.min(self.overflow.len());
+        for path in self.overflow.drain(..batch).collect::<Vec<_>>() {
+            self.push_entry(PathEntry::new(path));
+        }
+    }
+
+    /// Adds a search result, at its sorted position when `keep_sorted` is
+    /// set, otherwise at the end until `finish_search` sorts everything once.
+    pub fn push_entry(&mut self, mut entry: PathEntry) {
+        // what the user decided before for the same path wins
+        match self.mark_decisions.get(&entry.pathbuf) {
+            Some(marked) => entry.set_delete(*marked),
+            None if !self.config.mark_new_entries => entry.set_delete(false),
+            None => {}
+        }
+        if self.protection(&entry).is_some() {
+            entry.set_delete(false);
+        }
+        if let Some(err) = self.delete_errors.get(&entry.pathbuf) {
+            entry.error = Some(err.clone());
+        }
+        if entry.is_dir {
+            self.request_size(&entry.pathbuf);
+        }
+        if self.tree_view {
+            self.insert_in_tree(entry);
+            return;
+        }
+        let descending = self.sort_descending;
+        match self.sort {
+            Some(key) if self.config.keep_sorted => self
+                .list
+                .insert_sorted_by(entry, |a, b| key.compare_ordered(a, b, descending)),
+            _ => self.list.push(entry),
+        }
+    }
+
+    /// Queues `dir` for sizing in the background, unless it's known already.
+    fn request_size(&mut self, dir: &Path) {
+        if self.dir_sizes.contains_key(dir) || !self.pending_sizes.insert(dir.to_path_buf()) {
+            return;
+        }
+        self.size_scanner
+            .get_or_insert_with(SizeScanner::new)
+            .request(dir.to_path_buf());
+    }
+
+    /// Stores the directory sizes computed since the last call.
+    pub fn poll_sizes(&mut self) {
+        if let Some(scanner) = &self.size_scanner {
+            for (dir, size) in scanner.poll() {
+                self.pending_sizes.remove(&dir);
+                self.dir_sizes.insert(dir, size);
+            }
+        }
+    }
+
+    /// The size of a file, or the content size of a directory once it's known.
+    pub fn entry_size(&self, entry: &PathEntry) -> Option<u64> {
+        match entry.special {
+            Some(_) => None,
+            None if entry.is_dir => self.dir_sizes.get(&entry.pathbuf).copied(),
+            None => Some(entry.size),
+        }
+    }
+
+    /// Total size of the marked entries, and whether every directory among
+    /// them has been sized yet.
+    pub fn marked_size(&self) -> (u64, bool) {
+        self.marked_size_of(None)
+    }
+
+    /// Like `marked_size`, adding up only the marked entries in `only`.
+    pub fn marked_size_of(&self, only: Option<&HashSet<PathBuf>>) -> (u64, bool) {
+        self.marked_refs(only)
+            .into_iter()
+            .filter(|e| e.special.is_none())
+            .fold((0, true), |(total, complete), entry| {
+                match self.entry_size(entry) {
+                    Some(size) => (total + size, complete),
+                    None => (total, false),
+                }
+            })
+    }
+
+    pub fn finish_search(&mut self) {
+        self.searching = false;
+        if self.tree_view {
+            self.build_tree();
+        } else if !self.config.keep_sorted {
+            self.sort_entries();
+        }
+        if let Some(path) = self.reselect.take() {
+            self.select_by_path(&path);
+        }
+    }
+
+    pub fn cycle_sort(&mut self) {
+        self.sort = SortKey::cycle(self.sort);
+        self.sort_entries();
+    }
+
+    pub fn toggle_sort_direction(&mut self) {
+        self.sort_descending = !self.sort_descending;
+        self.sort_entries();
+    }
+
+    pub fn toggle_keep_sorted(&mut self) {
+        self.config.keep_sorted = !self.config.keep_sorted;
+        self.save_config();
+    }
+
+    fn sort_entries(&mut self) {
+        // the tree view keeps its own order
+        if self.tree_view {
+            return;
+        }
+        let descending = self.sort_descending;
+        if let Some(key) = self.sort {
+            self.list
+                .sort_by(|a, b| key.compare_ordered(a, b, descending));
+        }
+    }
+
+    pub fn toggle_delete(&mut self) {
+        let i = self.list.get_index();
+        if i.is_none() {
+            return;
+        }
+
+        let i = i.unwrap();
+        let before = self.mark_states(i..i + 1);
+
+        if self.list.items[i].group {
+            self.toggle_group(i);
+        } else {
+            self.list.items[i].toggle_delete();
+        }
+        self.unmark_protected();
+        self.remember_marks(i..i + 1);
+        self.record_marks(i..i + 1, before);
+    }
+
+    pub fn mark_all(&mut self) {
+        let before = self.mark_states(0..self.list.items.len());
+        for entry in self.list.items.iter_mut() {
+            entry.set_delete(true);
+        }
+        self.unmark_protected();
+        self.remember_marks(0..self.list.items.len());
+        self.record_marks(0..self.list.items.len(), before);
+    }
+
+    pub fn unmark_all(&mut self) {
+        let before = self.mark_states(0..self.list.items.len());
+        for entry in self.list.items.iter_mut() {
+            entry.set_delete(false);
+        }
+        self.remember_marks(0..self.list.items.len());
+        self.record_marks(0..self.list.items.len(), before);
+    }
+
+    pub fn invert_marks(&mut self) {
+        let before = self.mark_states(0..self.list.items.len());
+        for entry in self.list.items.iter_mut() {
+            entry.toggle_delete();
+        }
+        self.unmark_protected();
+        self.remember_marks(0..self.list.items.len());
+        self.record_marks(0..self.list.items.len(), before);
+    }
+
+    /// How the entries in `range` are marked, with the ones inside the
+    /// headings there, hidden ones included.
+    fn mark_states(&self, range: Range<usize>) -> Vec<(PathBuf, bool)> {
+        self.entries_in(range)
+            .into_iter()
+            .map(|entry| (entry.pathbuf.clone(), entry.is_delete()))
+            .collect()
+    }
+
+    /// Keeps the entries in `range` marked differently than `before` as a
+    /// step to undo.
+    fn record_marks(&mut self, range: Range<usize>, before: Vec<(PathBuf, bool)>) {
+        let now = self.mark_states(range);
+        let mut seen = HashSet::new();
+        let changes: Vec<(PathBuf, bool)> = before
+            .into_iter()
+            .zip(now)
+            .filter(|((_, marked), (_, now))| marked != now)
+            .map(|(before, _)| before)
+            .filter(|(path, _)| seen.insert(path.clone()))
+            .collect();
+        self.push_marks(changes);
+    }
+
+    /// Keeps `changes`, each entry changed with how it was marked before, as
+    /// a step to undo.
+    fn push_marks(&mut self, changes: Vec<(PathBuf, bool)>) {
+        if changes.is_empty() {
+            return;
+        }
+        if self.mark_undo.len() == MARK_UNDO_STEPS {
+            self.mark_undo.remove(0);
+        }
+        self.mark_undo.push(changes);
+        self.mark_redo.clear();
+    }
+
+    /// Marks the entries of `changes` as they were before when `undo`, as
+    /// they were after otherwise.
+    fn apply_marks(&mut self, changes: &[(PathBuf, bool)], undo: bool) {
+        let marks: HashMap<&Path, bool> = changes
+            .iter()
+            .map(|(path, before)| (path.as_path(), *before == undo))
+            .collect();
+        let entries = self
+            .list
+            .items
+            .iter_mut()
+            .chain(self.filtered_out.iter_mut().map(|(_, entry)| entry))
+            .chain(self.collapsed.values_mut().flatten());
+        for entry in entries {
+            if let Some(marked) = marks.get(entry.pathbuf.as_path()) {
+                entry.set_delete(*marked);
+            }
+        }
+        self.mark_decisions.extend(
+            marks
+                .into_iter()
+                .map(|(path, marked)| (path.to_path_buf(), marked)),
+        );
+    }
+
+    /// Undoes the last marking change, or the last deletion when no marks
+    /// changed since.
+    pub fn undo(&mut self) {
+        match self.mark_undo.pop() {
+            Some(changes) => {
+                self.apply_marks(&changes, true);
+                self.status = Some(format!("unmade {} mark changes", changes.len()));
+                self.mark_redo.push(changes);
+            }
+            None => self.undo_delete(),
+        }
+    }
+
+    /// Redoes the last marking change undone.
+    pub fn redo_marks(&mut self) {
+        match self.mark_redo.pop() {
+            Some(changes) => {
+                self.apply_marks(&changes, false);
+                self.status = Some(format!("redid {} mark changes", changes.len()));
+                self.mark_undo.push(changes);
+            }
+            None => self.status = Some(String::from("nothing to redo")),
+        }
+    }
+
+    /// Keeps how the entries in `range` are marked now, to mark them the
+    /// same way when a later search lists them again. Headings pass it on
+    /// to the entries inside them.
+    fn remember_marks(&mut self, range: Range<usize>) {
+        let decisions = self.mark_states(range);
+        self.mark_decisions.extend(decisions);
+    }
+
+    /// The entries in `range`, headings replaced by the entries inside them,
+    /// hidden ones included.
+    fn entries_in(&self, range: Range<usize>) -> Vec<&PathEntry> {
+        let mut entries: Vec<&PathEntry> = vec![];
+        for i in range {
+            let entry = &self.list.items[i];
+            if !entry.group {
+                entries.push(entry);
+                continue;
+            }
+            entries.extend(&self.list.items[self.children_range(i)]);
+            entries.extend(
+                self.collapsed
+                    .iter()
+                    .filter(|(path, _)| path.starts_with(&entry.pathbuf))
+                    .flat_map(|(_, hidden)| hidden),
+            );
+        }
+        entries.retain(|entry| !entry.group);
+        entries
+    }
+
+    /// Why the entry must never be deleted, see `protect::protected_reason`.
+    pub fn protection(&self, entry: &PathEntry) -> Option<String> {
+        protect::protected_reason(&entry.pathbuf, &self.config.protected_paths)
+    }
+
+    /// Unmarks the marked entries that are protected and lists them in a
+    /// popup. Returns whether there were any.
+    fn unmark_protected(&mut self) -> bool {
+        let mut lines = vec![];
+        for i in 0..self.list.items.len() {
+            let entry = &self.list.items[i];
+            if !entry.is_delete() {
+                continue;
+            }
+            if let Some(reason) = self.protection(entry) {
+                lines.push(format!("{}: {}", entry.pathbuf.display(), reason));
+                self.list.items[i].set_delete(false);
+            }
+        }
+        if lines.is_empty() {
+            return false;
+        }
+
+        lines.insert(
+            0,
+            String::from("These entries are protected and were left unmarked:"),
+        );
+        self.popup = Some(Popup {
+            title: String::from("Protected entries"),
+            lines,
+        });
+        true
+    }
+
+    /// Toggles the selected entry and the ones after it, `count` in total,
+    /// then selects the last one toggled.
+    pub fn toggle_delete_many(&mut self, count: usize) {
+        let start = match self.list.get_index() {
+            Some(i) => i,
+            None => return,
+        };
+
+        let end = start.saturating_add(count).min(self.list.items.len());
+        let before = self.mark_states(start..end);
+        for i in start..end {
+            match self.list.items[i].group {
+                true => self.toggle_group(i),
+                false => self.list.items[i].toggle_delete(),
+            }
+        }
+        self.unmark_protected();
+        self.remember_marks(start..end);
+        self.record_marks(start..end, before);
+        self.list.select_index(end - 1);
+    }
+
+    /// Keeps only the entries modified between `start` and `end`, inclusive.
+    /// Entries without a known modification time are dropped.
+    pub fn filter_by_time_range(&mut self, start: Option<SystemTime>, end: Option<SystemTime>) {
+        self.apply_time_range(&TimeRange {
+            start,
+            end,
+            outside: false,
+        });
+    }
+
+    pub fn apply_time_range(&mut self, range: &TimeRange) {
+        self.list
+            .retain(|e| e.modified.is_some_and(|t| range.contains(t)));
+        self.time_range = Some(range.clone());
+        self.status = Some(format!(
+            "{} entries left after the time filter",
+            self.list.items.len()
+        ));
+    }
+
+    pub fn toggle_histogram(&mut self) {
+        self.config.show_histogram = !self.config.show_histogram;
+        self.save_config();
+    }
+
+    /// Switches to the next built-in theme and keeps it in the config.
+    pub fn cycle_theme(&mut self) {
+        self.theme = self.theme.next();
+        self.config.theme = self.theme;
+        self.status = Some(format!("theme: {}", self.theme.name()));
+        self.save_config();
+    }
+
+    pub fn toggle_preview(&mut self) {
+        self.config.show_preview = !self.config.show_preview;
+        self.save_config();
+    }
+
+    /// Up to `max_lines` lines previewing the selected entry, read again
+    /// only once the selection moves.
+    pub fn selected_preview(&mut self, max_lines: usize) -> Option<&[String]> {
+        let path = self.selected_path()?.to_path_buf();
+        let stale = !matches!(&self.preview, Some((cached, lines, _)) if *cached == path && *lines == max_lines);
+        if stale {
+            let lines = preview::preview(&path, max_lines);
+            self.preview = Some((path, max_lines, lines));
+        }
+        self.preview.as_ref().map(|(_, _, lines)| lines.as_slice())
+    }
+
+    pub fn toggle_help(&mut self) {
+        self.config.show_help = !self.config.show_help;
+        self.save_config();
+    }
+
+    /// How the result list is drawn: compact once there are more than
+    /// `compact_above` results, unless a density was picked with `z`.
+    pub fn density(&self) -> Density {
+        let large =
+            self.config.compact_above > 0 && self.listed_count() > self.config.compact_above;
+        match large && !self.density_picked {
+            true => Density::Compact,
+            false => self.config.density,
+        }
+    }
+
+    pub fn toggle_density(&mut self) {
+        self.config.density = match self.density() {
+            Density::Comfortable => Density::Dense,
+            Density::Dense => Density::Compact,
+            Density::Compact => Density::Comfortable,
+        };
+        self.density_picked = true;
+        self.save_config();
+    }
+
+    /// Moves the split between the panes by `delta` percent, keeping both usable.
+    pub fn resize_split(&mut self, delta: i16) {
+        let ratio = self.config.split_ratio as i16 + delta;
+        self.config.split_ratio = ratio.clamp(20, 80) as u16;
+        self.save_config();
+    }
+
+    /// Whether deletions are for good, by the config or for this run.
+    pub fn permanent_delete(&self) -> bool {
+        self.config.permanent_delete || self.force_permanent
+    }
+
+    /// Whether deletions go to the quarantine rather than the trash, by the
+    /// config or for this run.
+    pub fn quarantine(&self) -> bool {
+        self.config.quarantine || self.force_quarantine
+    }
+
+    /// Whether the search skips ignored entries, by the config unless
+    /// toggled for this run.
+    pub fn respect_ignore(&self) -> bool {
+        self.config.respect_ignore != self.ignore_toggled
+    }
+
+    /// Whether the search root is watched for changes, by the config unless
+    /// toggled for this run.
+    pub fn watch_root(&self) -> bool {
+        self.config.watch != self.watch_toggled
+    }
+
+    /// Whether deletions go to the audit log, by the config and for this run.
+    pub fn log_deletions(&self) -> bool {
+        self.config.log_deletions && !self.skip_log
+    }
+
+    /// Persists layout changes so the next session starts the same way.
+    fn save_config(&mut self) {
+        if let Err(err) = self.config.save() {
+            self.status = Some(format!("could not save the config: {}", err));
+        }
+    }
+
+    /// Counts the listed entries per size bucket, smallest bucket first.
+    /// Directories not sized yet and special files are left out.
+    pub fn size_histogram(&self) -> Vec<(String, u64)> {
+        const KB: u64 = 1024;
+        const MB: u64 = 1024 * KB;
+        const GB: u64 = 1024 * MB;
+        let buckets = [
+            ("<1K", KB),
+            ("<1M", MB),
+            ("<10M", 10 * MB),
+            ("<100M", 100 * MB),
+            ("<1G", GB),
+            ("1G+", u64::MAX),
+        ];
+
+        let mut counts = [0; 6];
+        // sized like the Size column, directories by what they hold
+        let sizes = self
+            .list
+            .items
+            .iter()
+            .filter(|e| !e.group)
+            .filter_map(|e| self.entry_size(e));
+        for size in sizes {
+            let i = buckets
+                .iter()
+                .position(|(_, limit)| size < *limit)
+                .unwrap_or(buckets.len() - 1);
+            counts[i] += 1;
+        }
+
+        buckets
+            .iter()
+            .zip(counts)
+            .map(|((label, _), count)| (label.to_string(), count))
+            .collect()
+    }
+
+    /// An `rm -rf` invocation removing what deleting the marked entries
+    /// would, with the paths quoted for POSIX shells. Fails when nothing is
+    /// marked, or on a path that isn't UTF-8, which can't be written as is.
+    pub fn generate_delete_command(&self) -> Result<String, ErrorBox> {
+        let marked = self.marked_refs(None);
+        if marked.is_empty() {
+            return Err("nothing is marked for deletion".into());
+        }
+        let mut command = String::from("rm -rf --");
+        for entry in marked {
+            let path = entry
+                .pathbuf
+                .to_str()
+                .ok_or_else(|| format!("{} is not valid UTF-8", entry.pathbuf.to_string_lossy()))?;
+            command.push(' ');
+            command.push_str(&shell_quote(path));
+        }
+        Ok(command)
+    }
+
+    /// Runs `command` on the marked entries instead of deleting them, like
+    /// xargs, its output going to the log pane.
+    pub fn run_command(&mut self, command: &str) {
+        let command = command.trim();
+        if command.is_empty() {
+            return;
+        }
+        if self.command.is_some() {
+            self.status = Some(String::from("a command is already running"));
+            return;
+        }
+        let paths: Vec<PathBuf> = self
+            .entries_to_delete()
+            .into_iter()
+            .map(|e| e.pathbuf)
+            .collect();
+        if paths.is_empty() {
+            self.status = Some(String::from("nothing is marked to run the command on"));
+            return;
+        }
+
+        self.command_log = vec![format!("$ {} ({} entries)", command, paths.len())];
+        self.command = Some(CommandRun::start(
+            command.to_string(),
+            paths,
+            self.root.clone(),
+        ));
+    }
+
+    pub fn poll_command(&mut self) {
+        if let Some(run) = &self.command {
+            let messages = run.poll();
+            self.handle_command_messages(messages);
+        }
+    }
+
+    pub fn wait_for_command(&mut self) {
+        if let Some(run) = &self.command {
+            let messages = run.wait();
+            self.handle_command_messages(messages);
+        }
+    }
+
+    fn handle_command_messages(&mut self, messages: Vec<CommandMessage>) {
+        for message in messages {
+            match message {
+                CommandMessage::Output(line) => self.command_log.push(line),
+                CommandMessage::Done { failed } => {
+                    if let Some(run) = self.command.take() {
+                        self.status = Some(match failed {
+                            0 => format!("ran {} on {} entries", run.command, run.count),
+                            _ => format!("{} failed, see its output", run.command),
+                        });
+                    }
+                }
+            }
+        }
+        let excess = self.command_log.len().saturating_sub(COMMAND_LOG_LINES);
+        self.command_log.drain(..excess);
+    }
+
+    pub fn show_delete_command(&mut self) {
+        let command = match self.generate_delete_command() {
+            Ok(command) => command,
+            Err(err) => {
+                self.status = Some(format!("no shell command: {}", err));
+                return;
+            }
+        };
+        self.popup = Some(Popup {
+            title: String::from("Shell command"),
+            lines: vec![
+                command,
+                String::new(),
+                String::from("Warning: rm -rf is unrecoverable, files do not go to the trash"),
+            ],
+        });
+    }
+
+    pub fn inspect_selected(&self) -> Option<EntryDetails> {
+        let entry = &self.list.items[self.list.get_index()?];
+        let kind = match entry.special {
+            Some(kind) => kind,
+            None if entry.is_dir => "Dir",
+            None => "File",
+        };
+
+        Some(EntryDetails {
+            path: entry.pathbuf.clone(),
+            kind,
+            size: self.entry_size(entry),
+            modified: entry.modified,
+            permissions: entry.permissions.clone(),
+            symlink_target: fs::read_link(&entry.pathbuf).ok(),
+            matched_by: self.last_search.clone(),
+            case_sensitive: self.is_case_sensitive(),
+            marked: entry.is_delete(),
+            outside_root: !entry.pathbuf.starts_with(&self.root),
+            other_filesystem: self.is_cross_mount(entry),
+        })
+    }
+
+    /// Shows the details of the selected entry in a popup.
+    pub fn show_inspector(&mut self) {
+        let details = match self.inspect_selected() {
+            Some(details) => details,
+            None => return,
+        };
+
+        let mut lines = vec![
+            format!("Path: {}", details.path.display()),
+            format!("Type: {}", details.kind),
+            format!(
+                "Size: {}",
+                details.size.map_or(String::from("n/a"), humanize::size)
+            ),
+            format!(
+                "Modified: {}",
+                details.modified.map_or(String::from("?"), |time| format!(
+                    "{} ({})",
+                    humantime::format_rfc3339_seconds(time),
+                    humanize::age(time)
+                ))
+            ),
+            format!(
+                "Permissions: {}",
+                details
+                    .permissions
+                    .as_ref()
+                    .map_or(String::from("?"), humanize::permissions)
+            ),
+        ];
+        if let Some(target) = &details.symlink_target {
+            lines.push(format!("Symlink to: {}", target.display()));
+        }
+        if let Some((engine, pattern)) = &details.matched_by {
+            let case = match details.case_sensitive {
+                true => "case sensitive",
+                false => "case insensitive",
+            };
+            lines.push(format!(
+                "Matched by: {} '{}', {}",
+                engine.name(),
+                pattern,
+                case
+            ));
+        }
+        lines.push(format!(
+            "Marked for deletion: {}",
+            if details.marked { "yes" } else { "no" }
+        ));
+        if details.outside_root {
+            lines.push(String::from("Outside of the search root"));
+        }
+        if details.other_filesystem {
+            lines.push(String::from("On another filesystem than the search root"));
+        }
+
+        self.popup = Some(Popup {
+            title: String::from("Inspector"),
+            lines,
+        });
+    }
+
+    pub fn select_next_dir(&mut self) {
+        let len = self.list.items.len();
+        let start = self.list.get_index().unwrap_or(len.saturating_sub(1));
+        let next = (1..=len)
+            .map(|offset| (start + offset) % len)
+            .find(|i| self.list.items[*i].is_dir);
+        if let Some(i) = next {
+            self.list.select_index(i);
+        }
+    }
+
+    pub fn select_prev_dir(&mut self) {
+        let len = self.list.items.len();
+        let start = self.list.get_index().unwrap_or(0);
+        let prev = (1..=len)
+            .map(|offset| (start + len - offset) % len)
+            .find(|i| self.list.items[*i].is_dir);
+        if let Some(i) = prev {
+            self.list.select_index(i);
+        }
+    }
+
+    /// Replaces the selected directory with its immediate children, which
+    /// keep its mark. Unreadable directories are left in place, and a
+    /// collapsed one in the tree view is just shown again.
+    pub fn expand_selected_dir(&mut self) -> Result<(), ErrorBox> {
+        if self.expand_collapsed() {
+            return Ok(());
+        }
+        let i = match self.list.get_index() {
+            Some(i) => i,
+            None => return Ok(()),
+        };
+        let dir = &self.list.items[i];
+        if !dir.is_dir {
+            self.status = Some(format!(
+                "not a directory: {}",
+                self.display_path(dir).display()
+            ));
+            return Ok(());
+        }
+
+        let read_dir = match fs::read_dir(&dir.pathbuf) {
+            Ok(read_dir) => read_dir,
+            Err(err) => {
+                self.status = Some(format!("could not read {}: {}", dir.pathbuf.display(), err));
+                return Ok(());
+            }
+        };
+        let is_delete = dir.is_delete();
+        let mut children: Vec<PathEntry> = read_dir
+            .filter_map(Result::ok)
+            .map(|child| {
+                let mut entry = PathEntry::new(child.path());
+                entry.set_delete(is_delete && self.protection(&entry).is_none());
+                entry
+            })
+            .collect();
+        if children.is_empty() {
+            self.status = Some(format!("{} is empty", dir.pathbuf.display()));
+            return Ok(());
+        }
+
+        children.sort_by(|a, b| a.pathbuf.cmp(&b.pathbuf));
+        for child in children.iter().filter(|child| child.is_dir) {
+            self.request_size(&child.pathbuf);
+        }
+        self.list.items.splice(i..=i, children);
+        if self.tree_view {
+            self.build_tree();
+        }
+        Ok(())
+    }
+
+    /// Starts looking for duplicates among the listed files, see
+    /// `duplicates::find`.
+    pub fn find_duplicates(&mut self) {
+        if self.searching {
+            self.status = Some(String::from("wait for the search to finish"));
+            return;
+        }
+        let files = self
+            .list
+            .items
+            .iter()
+            .filter(|e| e.is_file && !e.group)
+            .map(|e| e.pathbuf.clone())
+            .collect();
+        self.duplicate_finder = Some(DuplicateFinder::start(files));
+        self.status = Some(String::from("looking for duplicates"));
+    }
+
+    pub fn is_finding_duplicates(&self) -> bool {
+        self.duplicate_finder.is_some()
+    }
+
+    pub fn poll_duplicates(&mut self) {
+        if let Some(groups) = self
+            .duplicate_finder
+            .as_ref()
+            .and_then(DuplicateFinder::poll)
+        {
+            self.show_duplicates(groups);
+        }
+    }
+
+    pub fn wait_for_duplicates(&mut self) {
+        if let Some(finder) = self.duplicate_finder.as_ref() {
+            let groups = finder.wait();
+            self.show_duplicates(groups);
+        }
+    }
+
+    /// Lists only the duplicates, a group after the other, with every copy
+    /// but the first marked.
+    fn show_duplicates(&mut self, groups: Vec<Vec<PathBuf>>) {
+        self.duplicate_finder = None;
+        if groups.is_empty() {
+            self.status = Some(String::from("no duplicates found"));
+            return;
+        }
+        if self.tree_view {
+            self.toggle_tree_view();
+        }
+        self.filter.clear();
+        self.mark_view = MarkView::All;
+        self.filtered_out.clear();
+
+        let mut entries: HashMap<PathBuf, PathEntry> = std::mem::take(&mut self.list.items)
+            .into_iter()
+            .map(|e| (e.pathbuf.clone(), e))
+            .collect();
+        self.duplicate_groups.clear();
+        let mut copies = 0;
+        for (n, group) in groups.iter().enumerate() {
+            for (i, path) in group.iter().enumerate() {
+                let mut entry = entries
+                    .remove(path)
+                    .unwrap_or_else(|| PathEntry::new(path.clone()));
+                entry.set_delete(i > 0);
+                copies += (i > 0) as usize;
+                self.duplicate_groups.insert(path.clone(), n + 1);
+                self.list.items.push(entry);
+            }
+        }
+        self.list.select_index(0);
+        self.status = Some(format!(
+            "{} duplicate groups, {} extra copies marked",
+            groups.len(),
+            copies
+        ));
+        self.unmark_protected();
+    }
+
+    pub fn toggle_tree_view(&mut self) {
+        self.expand_all();
+        self.tree_view = !self.tree_view;
+        if self.tree_view {
+            self.build_tree();
+        } else {
+            let selected = self.selected_path().map(Path::to_path_buf);
+            self.list.retain(|e| !e.group);
+            self.sort_entries();
+            if let Some(path) = selected {
+                self.select_by_path(&path);
+            }
+        }
+    }
+
+    /// Sorts the entries by path and adds a heading for each parent
+    /// directory that isn't listed itself, so every entry follows the
+    /// directory holding it.
+    fn build_tree(&mut self) {
+        let selected = self.selected_path().map(Path::to_path_buf);
+        self.list.items.retain(|e| !e.group);
+        let listed: HashSet<PathBuf> = self.list.items.iter().map(|e| e.pathbuf.clone()).collect();
+        let parents: BTreeSet<PathBuf> = self
+            .list
+            .items
+            .iter()
+            .filter_map(|e| e.pathbuf.parent())
+            .filter(|parent| self.needs_group(parent) && !listed.contains(*parent))
+            .map(Path::to_path_buf)
+            .collect();
+        self.list
+            .items
+            .extend(parents.into_iter().map(PathEntry::group));
+        self.list.sort_by(|a, b| a.pathbuf.cmp(&b.pathbuf));
+        match selected {
+            Some(path) => {
+                self.select_by_path(&path);
+            }
+            None => self.list.select_index(0),
+        }
+    }
+
+    /// Entries right in the root need no heading.
+    fn needs_group(&self, dir: &Path) -> bool {
+        dir != self.root && !dir.as_os_str().is_empty()
+    }
+
+    fn insert_in_tree(&mut self, entry: PathEntry) {
+        if let Some(parent) = entry.pathbuf.parent() {
+            let missing =
+                self.needs_group(parent) && !self.list.items.iter().any(|e| e.pathbuf == parent);
+            if missing {
+                let group = PathEntry::group(parent.to_path_buf());
+                self.list
+                    .insert_sorted_by(group, |a, b| a.pathbuf.cmp(&b.pathbuf));
+            }
+        }
+        self.list
+            .insert_sorted_by(entry, |a, b| a.pathbuf.cmp(&b.pathbuf));
+    }
+
+    /// How deep the entry sits below the root in the tree view.
+    pub fn tree_depth(&self, entry: &PathEntry) -> usize {
+        self.display_path(entry)
+            .components()
+            .count()
+            .saturating_sub(1)
+    }
+
+    /// Range of the entries listed right after the one at `i` that live
+    /// inside it.
+    fn children_range(&self, i: usize) -> std::ops::Range<usize> {
+        let dir = &self.list.items[i].pathbuf;
+        let end = self.list.items[i + 1..]
+            .iter()
+            .position(|e| !e.pathbuf.starts_with(dir))
+            .map_or(self.list.items.len(), |n| i + 1 + n);
+        i + 1..end
+    }
+
+    /// Marks everything inside the heading at `i`, hidden entries included,
+    /// or unmarks it all when it's all marked already.
+    fn toggle_group(&mut self, i: usize) {
+        let range = self.children_range(i);
+        let dir = self.list.items[i].pathbuf.clone();
+        let hidden: Vec<PathBuf> = self
+            .collapsed
+            .keys()
+            .filter(|path| path.starts_with(&dir))
+            .cloned()
+            .collect();
+        let (marked, total) = self.group_marks(i);
+        let mark = marked < total;
+        for entry in &mut self.list.items[range] {
+            entry.set_delete(mark);
+        }
+        for path in hidden {
+            for entry in self.collapsed.get_mut(&path).into_iter().flatten() {
+                entry.set_delete(mark);
+            }
+        }
+    }
+
+    /// How many entries inside the directory at `i` are marked, out of how
+    /// many, hidden ones included.
+    pub fn group_marks(&self, i: usize) -> (usize, usize) {
+        let dir = &self.list.items[i].pathbuf;
+        let hidden = self
+            .collapsed
+            .iter()
+            .filter(|(path, _)| path.starts_with(dir))
+            .flat_map(|(_, entries)| entries);
+        self.list.items[self.children_range(i)]
+            .iter()
+            .chain(hidden)
+            .filter(|e| !e.group)
+            .fold((0, 0), |(marked, total), e| {
+                (marked + e.is_delete() as usize, total + 1)
+            })
+    }
+
+    /// Entries hidden under the directory at `path`, if it's collapsed.
+    pub fn collapsed_count(&self, path: &Path) -> Option<usize> {
+        self.collapsed.get(path)?;
+        let hidden = self
+            .collapsed
+            .iter()
+            .filter(|(dir, _)| dir.starts_with(path))
+            .flat_map(|(_, entries)| entries)
+            .filter(|e| !e.group)
+            .count();
+        Some(hidden)
+    }
+
+    /// Hides what's inside the selected directory of the tree view. On an
+    /// entry with nothing inside, the directory holding it is collapsed.
+    pub fn collapse_selected(&mut self) {
+        let mut i = match self.list.get_index() {
+            Some(i) if self.tree_view => i,
+            _ => return,
+        };
+        let mut range = self.children_range(i);
+        if range.is_empty() {
+            let path = &self.list.items[i].pathbuf;
+            let parent = self.list.items[..i]
+                .iter()
+                .rposition(|e| path.starts_with(&e.pathbuf));
+            match parent {
+                Some(parent) => i = parent,
+                None => return,
+            }
+            range = self.children_range(i);
+        }
+        let hidden: Vec<PathEntry> = self.list.items.drain(range).collect();
+        let path = self.list.items[i].pathbuf.clone();
+        self.collapsed.entry(path).or_default().extend(hidden);
+        self.list.select_index(i);
+    }
+
+    /// Shows again what's inside the selected directory, returns whether
+    /// it was collapsed.
+    pub fn expand_collapsed(&mut self) -> bool {
+        let i = match self.list.get_index() {
+            Some(i) => i,
+            None => return false,
+        };
+        match self.collapsed.remove(&self.list.items[i].pathbuf) {
+            Some(hidden) => {
+                self.list.items.splice(i + 1..i + 1, hidden);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Shows every entry hidden under a collapsed directory.
+    fn expand_all(&mut self) {
+        if self.collapsed.is_empty() {
+            return;
+        }
+        let selected = self.selected_path().map(Path::to_path_buf);
+        for (_, hidden) in self.collapsed.drain() {
+            self.list.items.extend(hidden);
+        }
+        self.list.sort_by(|a, b| a.pathbuf.cmp(&b.pathbuf));
+        if let Some(path) = selected {
+            self.select_by_path(&path);
+        }
+    }
+
+    /// Selects the entry at `path`, returns whether there is one.
+    pub fn select_by_path(&mut self, path: &Path) -> bool {
+        match self.list.items.iter().position(|e| e.pathbuf == path) {
+            Some(i) => {
+                self.list.select_index(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Asks for the selected entry to be opened with `opener`, which the
+    /// interface does once it has left the terminal to it.
+    pub fn request_open(&mut self, opener: Opener) {
+        match self.list.get_index().map(|i| &self.list.items[i]) {
+            Some(entry) if !entry.group => {
+                self.open_request = Some((entry.pathbuf.clone(), opener));
+            }
+            _ => self.status = Some(String::from("nothing selected to open")),
+        }
+    }
+
+    /// Copies the path of the selected entry to the clipboard.
+    pub fn yank_selected(&mut self) {
+        match self.list.get_index().map(|i| &self.list.items[i]) {
+            Some(entry) if !entry.group => {
+                self.clipboard = Some(entry.pathbuf.display().to_string());
+                self.status = Some(String::from("copied the path"));
+            }
+            _ => self.status = Some(String::from("nothing selected to copy")),
+        }
+    }
+
+    /// Copies the paths of the marked entries to the clipboard, one a line.
+    pub fn yank_marked(&mut self) {
+        let paths: Vec<String> = self
+            .get_entries_by(|e| e.is_delete())
+            .iter()
+            .map(|e| e.pathbuf.display().to_string())
+            .collect();
+        if paths.is_empty() {
+            self.status = Some(String::from("nothing is marked to copy"));
+            return;
+        }
+        self.status = Some(format!("copied {} paths", paths.len()));
+        self.clipboard = Some(paths.join("\n"));
+    }
+
+    pub fn selected_path(&self) -> Option<&Path> {
+        let i = self.list.get_index()?;
+        self.list.items.get(i).map(|e| e.pathbuf.as_path())
+    }
+
+    pub fn get_entries_by<P>(&self, mut predicate: P) -> Vec<PathEntry>
+    where
+        P: FnMut(&PathEntry) -> bool,
+    {
+        self.list
+            .items
+            .iter()
+            .filter(|e| predicate(e))
+            .cloned()
+            .collect::<Vec<_>>()
+    }
+
+    /// Marked entries below another marked entry, deleted along with it.
+    pub fn covered_paths(&self) -> HashSet<&Path> {
+        let marked: HashSet<&Path> = self
+            .list
+            .items
+            .iter()
+            .filter(|e| e.is_delete())
+            .map(|e| e.pathbuf.as_path())
+            .collect();
+        marked
+            .iter()
+            .filter(|path| path.ancestors().skip(1).any(|a| marked.contains(a)))
+            .copied()
+            .collect()
+    }
+
+    /// The marked entries that are not covered by a marked parent, what a
+    /// deletion actually removes.
+    pub fn entries_to_delete(&self) -> Vec<PathEntry> {
+        self.marked_entries(None)
+    }
+
+    /// Like `entries_to_delete`, taking only the marked entries in `only`.
+    fn marked_entries(&self, only: Option<&HashSet<PathBuf>>) -> Vec<PathEntry> {
+        self.marked_refs(only).into_iter().cloned().collect()
+    }
+
+    fn marked_refs(&self, only: Option<&HashSet<PathBuf>>) -> Vec<&PathEntry> {
+        let marked: HashSet<&Path> = self
+            .list
+            .items
+            .iter()
+            .filter(|e| e.is_delete() && only.is_none_or(|only| only.contains(&e.pathbuf)))
+            .map(|e| e.pathbuf.as_path())
+            .collect();
+        self.list
+            .items
+            .iter()
+            .filter(|e| {
+                marked.contains(e.pathbuf.as_path())
+                    && !e.pathbuf.ancestors().skip(1).any(|a| marked.contains(a))
+            })
+            .collect()
+    }
+
+    /// Moves the marked entries to the trash, or the quarantine when
+    /// `quarantine` is set, or deletes them for good when `permanent`.
+    pub fn delete_active_entries(&mut self, permanent: bool) -> Result<(), ErrorBox> {
+        self.delete_marked(permanent, None)
+    }
+
+    /// Like `delete_active_entries`, deleting only the marked entries in
+    /// `only` when given.
+    fn delete_marked(
+        &mut self,
+        permanent: bool,
+        only: Option<&HashSet<PathBuf>>,
+    ) -> Result<(), ErrorBox> {
+        // nothing protected can be marked, but better safe than sorry
+        if self.unmark_protected() {
+            return Ok(());
+        }
+        if self.dry_run {
+            self.show_dry_run_report();
+            return Ok(());
+        }
+        // marked children go with their parent, deleting them first or after
+        // would only fail on the parent or on the children
+        let pending = self.marked_entries(only);
+        self.delete_entries(pending, permanent, None)
+    }
+
+    /// Deletes `pending` like `delete_active_entries`, `archive` holding a
+    /// copy of them.
+    fn delete_entries(
+        &mut self,
+        pending: Vec<PathEntry>,
+        permanent: bool,
+        archive: Option<PathBuf>,
+    ) -> Result<(), ErrorBox> {
+        let disposal = match (permanent, self.quarantine()) {
+            (true, _) => Disposal::Permanent,
+            (false, true) => {
+                let base = quarantine::dir().ok_or("no data directory on this platform")?;
+                let batch = quarantine::create_batch(&base, SystemTime::now())
+                    .map_err(|err| format!("could not create the quarantine: {}", err))?;
+                // written before anything moves, so what a crash leaves in
+                // the batch can still be restored
+                let planned = pending
+                    .iter()
+                    .map(|e| (e.pathbuf.clone(), e.size))
+                    .collect();
+                write_quarantine_manifest(&batch, &self.root, &planned)
+                    .map_err(|err| format!("could not write the quarantine manifest: {}", err))?;
+                Disposal::Quarantine {
+                    batch,
+                    root: self.root.clone(),
+                }
+            }
+            (false, false) => Disposal::Trash,
+        };
+
+        self.delete_errors.clear();
+        self.batch = Some(DeleteBatch {
+            total: pending.len(),
+            pending: pending.into(),
+            removed: HashMap::new(),
+            freed: 0,
+            disposal,
+            archive,
+            failed: vec![],
+            started: Instant::now(),
+            current: None,
+            deletion: None,
+            cancelled: false,
+        });
+        self.resume_delete(None);
+        Ok(())
+    }
+
+    /// Asks for confirmation before archiving the marked entries and deleting
+    /// them.
+    pub fn request_archive(&mut self) {
+        self.expand_all();
+        let marked = self.entries_to_delete();
+        if marked.is_empty() {
+            self.status = Some(String::from("nothing is marked to archive"));
+            return;
+        }
+        self.confirm = Some(ConfirmAction::Archive {
+            count: marked.len(),
+        });
+        self.set_app_mode(AppMode::Confirm);
+    }
+
+    /// Writes the marked entries to a timestamped tarball in `archive_dir`
+    /// on a worker thread. They are deleted once the archive checks out.
+    pub fn archive_marked(&mut self) -> Result<(), ErrorBox> {
+        if self.unmark_protected() {
+            return Ok(());
+        }
+        if self.dry_run {
+            self.show_dry_run_report();
+            return Ok(());
+        }
+
+        let dir = match &self.config.archive_dir {
+            Some(dir) => dir.clone(),
+            None => archive::dir().ok_or("no data directory on this platform")?,
+        };
+        let paths: Vec<PathBuf> = self
+            .entries_to_delete()
+            .into_iter()
+            .map(|e| e.pathbuf)
+            .collect();
+        let dest = dir.join(archive::file_name(SystemTime::now()));
+        self.archiver = Some(Archiver::start(paths, self.root.clone(), dest));
+        Ok(())
+    }
+
+    pub fn poll_archive(&mut self) -> Result<(), ErrorBox> {
+        match self.archiver.as_ref().and_then(Archiver::poll) {
+            Some(result) => self.finish_archive(result),
+            None => Ok(()),
+        }
+    }
+
+    pub fn wait_for_archive(&mut self) -> Result<(), ErrorBox> {
+        match self.archiver.as_ref().map(Archiver::wait) {
+            Some(result) => self.finish_archive(result),
+            None => Ok(()),
+        }
+    }
+
+    fn finish_archive(&mut self, result: Result<(), String>) -> Result<(), ErrorBox> {
+        let archiver = match self.archiver.take() {
+            Some(archiver) => archiver,
+            None => return Ok(()),
+        };
+        match result {
+            Ok(()) => {
+                // what's marked may have changed since, only what's in the
+                // archive goes
+                let archived: HashSet<PathBuf> = archiver.paths.into_iter().collect();
+                let pending = self.get_entries_by(|e| archived.contains(&e.pathbuf));
+                self.delete_entries(pending, self.permanent_delete(), Some(archiver.path))
+            }
+            Err(err) => {
+                self.status = Some(format!("could not archive, nothing was deleted: {}", err));
+                Ok(())
+            }
+        }
+    }
+
+    /// Restores the entries of the last deletion from the trash and lists
+    /// them again. Permanent deletions can't be undone.
+    pub fn undo_delete(&mut self) {
+        if self.last_trashed.is_empty() {
+            self.status = Some(String::from("nothing to undo"));
+            return;
+        }
+
+        match undo::restore(&self.last_trashed) {
+            Ok(()) => {
+                let paths = std::mem::take(&mut self.last_trashed);
+                self.status = Some(format!("restored {} entries from the trash", paths.len()));
+                for path in paths {
+                    if !self.list.items.iter().any(|e| e.pathbuf == path) {
+                        self.push_entry(PathEntry::new(path));
+                    }
+                }
+            }
+            Err(err) => self.status = Some(format!("could not undo: {}", err)),
+        }
+    }
+
+    pub fn dry_run_report(&self) -> DryRunReport {
+        let marked = self.entries_to_delete();
+        let entries: Vec<(PathBuf, u64)> = marked
+            .iter()
+            .map(|e| (e.pathbuf.clone(), e.disk_size()))
+            .collect();
+        DryRunReport {
+            files: marked.iter().filter(|e| !e.is_dir).count(),
+            dirs: marked.iter().filter(|e| e.is_dir).count(),
+            total_size: entries.iter().map(|(_, size)| size).sum(),
+            entries,
+        }
+    }
+
+    /// The dry run report with the type and modification time of each entry,
+    /// for scripts.
+    pub fn dry_run_document(&self) -> DryRunDocument {
+        let report = self.dry_run_report();
+        let listed: HashMap<&Path, &PathEntry> = self
+            .list
+            .items
+            .iter()
+            .map(|e| (e.pathbuf.as_path(), e))
+            .collect();
+        let entries = report
+            .entries
+            .iter()
+            .filter_map(|(path, size)| {
+                Some(ExportedEntry::new(listed.get(path.as_path())?, Some(*size)))
+            })
+            .collect();
+        DryRunDocument {
+            root: self.root.clone(),
+            pattern: self.pattern.content.clone(),
+            files: report.files,
+            dirs: report.dirs,
+            total_size: report.total_size,
+            entries,
+        }
+    }
+
+    /// Shows the dry run report, and writes it to `dry_run_report` if set.
+    fn show_dry_run_report(&mut self) {
+        let lines = self.dry_run_report().lines();
+        if let Some(path) = &self.dry_run_report {
+            let mut content = lines.join("\n");
+            content.push('\n');
+            if let Err(err) = fs::write(path, content) {
+                self.status = Some(format!("could not write {}: {}", path.display(), err));
+            }
+        }
+        self.popup = Some(Popup {
+            title: String::from("Dry run"),
+            lines,
+        });
+    }
+
+    /// Writes the summary of the last deletion next to the deletion log.
+    pub fn export_summary(&mut self, lines: &[String]) {
+        self.status = Some(match log::write_report(lines) {
+            Ok(path) => format!("report written to {}", path.display()),
+            Err(err) => format!("could not export the report: {}", err),
+        });
+    }
+
+    pub fn toggle_dry_run(&mut self) {
+        self.dry_run = !self.dry_run;
+    }
+
+    /// Deletes the pending entries of the batch on a worker thread, pausing
+    /// on each directory when `per_dir_confirm` is set. `approved` is the
+    /// directory just confirmed.
+    fn resume_delete(&mut self, approved: Option<PathEntry>) {
+        let mut batch = match self.batch.take() {
+            Some(batch) => batch,
+            None => return,
+        };
+        if batch.is_running() {
+            self.batch = Some(batch);
+            return;
+        }
+
+        // everything up to the next directory to confirm
+        let mut run: Vec<PathEntry> = approved.into_iter().collect();
+        while let Some(entry) = batch.pending.front() {
+            if entry.is_dir && self.config.per_dir_confirm {
+                break;
+            }
+            run.extend(batch.pending.pop_front());
+        }
+
+        if !run.is_empty() {
+            let threads = match self.config.per_dir_confirm {
+                true => 1,
+                false => self.config.delete_threads,
+            };
+            batch.deletion = Some(Deletion::start(run, batch.disposal.clone(), threads));
+            self.batch = Some(batch);
+            return;
+        }
+
+        if let Some(entry) = batch.pending.front() {
+            let (children, size) = dir_stats(&entry.pathbuf);
+            self.confirm = Some(ConfirmAction::DeleteDir {
+                path: entry.pathbuf.clone(),
+                children,
+                size,
+            });
+            self.set_app_mode(AppMode::Confirm);
+            self.batch = Some(batch);
+            return;
+        }
+
+        self.finish_delete(batch);
+    }
+
+    fn finish_delete(&mut self, batch: DeleteBatch) {
+        // a search run again would list the duplicates, or whatever else the
+        // list was narrowed down to, in full
+        let refresh = self.config.refresh_after_delete
+            && !batch.removed.is_empty()
+            && !self.pattern.content.is_empty()
+            && !self.should_quit
+            && !self.is_narrowed();
+        // entries below a removed directory are gone too, hidden ones included
+        let gone = |path: &Path| path.ancestors().any(|a| batch.removed.contains_key(a));
+        self.list.retain(|e| !gone(&e.pathbuf));
+        self.filtered_out.retain(|(_, e)| !gone(&e.pathbuf));
+        for hidden in self.collapsed.values_mut() {
+            hidden.retain(|e| !gone(&e.pathbuf));
+        }
+        self.overflow.retain(|path| !gone(path));
+        // drop the headings left without entries
+        if self.tree_view {
+            self.build_tree();
+        }
+        for (path, err) in batch.failed.iter() {
+            if let Some(entry) = self.list.items.iter_mut().find(|e| e.pathbuf == *path) {
+                entry.error = Some(err.clone());
+            }
+        }
+        self.delete_errors.extend(batch.failed.iter().cloned());
+        let mut summary = batch.summary();
+        let mut lines = summary.clone();
+        lines.extend([
+            String::new(),
+            String::from("[e] export, any other key closes"),
+        ]);
+        self.popup = Some(Popup {
+            title: String::from("Deletion summary"),
+            lines,
+        });
+        summary.insert(0, format!("Deleted from {}", self.root.display()));
+        self.delete_summary = Some(summary);
+        let outcome = match batch.cancelled {
+            true => "deletion cancelled, eradicated",
+            false => "eradicated",
+        };
+        self.status = Some(match batch.failed.first() {
+            None => format!("{} {} entries", outcome, batch.removed.len()),
+            Some((path, err)) => format!(
+                "{} {} entries, {} failed ({}: {})",
+                outcome,
+                batch.removed.len(),
+                batch.failed.len(),
+                path.display(),
+                err
+            ),
+        });
+        // undo goes to the deletion now
+        if !batch.removed.is_empty() {
+            self.mark_undo.clear();
+            self.mark_redo.clear();
+        }
+        match &batch.disposal {
+            Disposal::Trash if !batch.removed.is_empty() => {
+                self.last_trashed = batch.removed.keys().cloned().collect();
+            }
+            Disposal::Quarantine { batch: dir, root } => {
+                if let Err(err) = write_quarantine_manifest(dir, root, &batch.removed) {
+                    self.status = Some(format!("could not write the quarantine manifest: {}", err));
+                }
+            }
+            _ => {}
+        }
+        if self.log_deletions() {
+            let records = batch.audit_records(SystemTime::now());
+            if !records.is_empty() {
+                if let Err(err) = log::append(&records, self.config.log_max_size.0) {
+                    self.status = Some(format!("could not log the deletion: {}", err));
+                }
+            }
+        }
+        // emptied directories and the like may match now, what's listed
+        // already comes back marked as it is
+        if refresh {
+            self.remember_marks(0..self.list.items.len());
+            if let Err(err) = self.refresh() {
+                self.status = Some(format!("could not refresh the results: {}", err));
+            }
+        }
+    }
+
+    /// Whether the list shows only part of what the search found, like the
+    /// duplicates among it or what a filter lets through.
+    pub fn is_narrowed(&self) -> bool {
+        !self.duplicate_groups.is_empty()
+            || self.time_range.is_some()
+            || !self.filter.is_empty()
+            || self.mark_view != MarkView::All
+    }
+
+    pub fn is_deleting(&self) -> bool {
+        self.batch.as_ref().is_some_and(DeleteBatch::is_running)
+    }
+
+    /// Takes in the progress of the running deletion.
+    pub fn poll_delete(&mut self) {
+        let messages = match self.batch.as_ref().and_then(|b| b.deletion.as_ref()) {
+            Some(deletion) => deletion.poll(),
+            None => return,
+        };
+        self.handle_delete_messages(messages);
+    }
+
+    /// Blocks until the batch is done or waits for a confirmation, for use
+    /// without an event loop.
+    pub fn wait_for_delete(&mut self) {
+        while let Some(deletion) = self.batch.as_ref().and_then(|b| b.deletion.as_ref()) {
+            let messages = deletion.wait();
+            self.handle_delete_messages(messages);
+        }
+    }
+
+    /// Stops the running deletion after the entries in progress, the rest
+    /// of the batch stays.
+    pub fn cancel_delete(&mut self) {
+        if let Some(deletion) = self.batch.as_ref().and_then(|b| b.deletion.as_ref()) {
+            deletion.cancel();
+        }
+    }
+
+    fn handle_delete_messages(&mut self, messages: Vec<DeleteMessage>) {
+        let batch = match self.batch.as_mut() {
+            Some(batch) => batch,
+            None => return,
+        };
+
+        let mut done = false;
+        for message in messages {
+            done |= batch.apply(message);
+        }
+
+        if done {
+            if batch.deletion.take().is_some_and(|d| d.is_cancelled()) {
+                batch.cancelled = true;
+                batch.pending.clear();
+            }
+            batch.current = None;
+            self.resume_delete(None);
+        }
+    }
+}
+
+/// Identifies the filesystem `path` lives on: the device id on unix, the
+/// drive letter on windows.
+#[cfg(unix)]
+pub fn filesystem_id(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::metadata(path).ok().map(|m| m.dev())
+}
+
+#[cfg(windows)]
+pub fn filesystem_id(path: &Path) -> Option<u64> {
+    use std::path::{Component, Prefix};
+
+    match path.components().next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+                Some(letter.to_ascii_uppercase() as u64)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn filesystem_id(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Names the kind of a file that is neither regular, a directory nor a
+/// symlink: FIFOs, sockets and devices. Those are never read.
+#[cfg(unix)]
+pub fn special_kind(file_type: &FileType) -> Option<&'static str> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if file_type.is_fifo() {
+        Some("FIFO")
+    } else if file_type.is_socket() {
+        Some("Socket")
+    } else if file_type.is_block_device() || file_type.is_char_device() {
+        Some("Device")
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+pub fn special_kind(_file_type: &FileType) -> Option<&'static str> {
+    None
+}
+
+/// Counts everything below `path` and sums the sizes of the regular files in
+/// there. Nothing is opened, so special files can't block the traversal.
+pub fn dir_stats(path: &Path) -> (u64, u64) {
+    WalkDir::new(path)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(Result::ok)
+        .fold((0, 0), |(children, size), entry| {
+            let len = match entry.metadata() {
+                Ok(metadata) if metadata.is_file() => metadata.len(),
+                _ => 0,
+            };
+            (children + 1, size + len)
+        })
+}
+
+/// The glob patterns of a pattern input, separated by spaces or commas.
+/// Commas within `{a,b}` alternates belong to the pattern.
+pub fn split_patterns(pattern: &str) -> Vec<String> {
+    piece_spans(pattern)
+        .into_iter()
+        .map(|span| pattern[span].to_string())
+        .collect()
+}
+
+/// Where each pattern of `split_patterns` is in `pattern`.
+fn piece_spans(pattern: &str) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    let mut start = None;
+    let mut depth = 0usize;
+    for (i, ch) in pattern.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        if ch.is_whitespace() || (ch == ',' && depth == 0) {
+            if let Some(start) = start.take() {
+                spans.push(start..i);
+            }
+        } else {
+            start.get_or_insert(i);
+        }
+    }
+    if let Some(start) = start {
+        spans.push(start..pattern.len());
+    }
+    spans
+}
+
+/// Splits `!pattern` words off a search pattern, like `**/*.log !important/*`.
+/// Returns what's left of the pattern and the exclude patterns, without the `!`.
+pub fn split_excludes(pattern: &str) -> (String, Vec<String>) {
+    let (excludes, rest): (Vec<&str>, Vec<&str>) = pattern
+        .split_whitespace()
+        .partition(|word| word.len() > 1 && word.starts_with('!'));
+    let excludes = excludes.iter().map(|word| word[1..].to_string()).collect();
+    (rest.join(" "), excludes)
+}
+
+fn compile_excludes(excludes: &[String], case_sensitive: bool) -> Result<GlobSet, ErrorBox> {
+    // `!*.log` leaves out logs at any depth
+    search::compile(excludes, case_sensitive, false)
+        .map_err(|err| format!("invalid exclude: {}", err).into())
+}
+
+/// Whether the characters of `query` appear in `text` in order, ignoring case.
+pub fn fuzzy_match(query: &str, text: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|ch| text.any(|other| other == ch))
+}
+
+/// Quotes `s` for a POSIX shell, leaving it bare when that's already safe.
+pub fn shell_quote(s: &str) -> String {
+    let is_safe = |ch: char| ch.is_ascii_alphanumeric() || "_-./+=:@%".contains(ch);
+    if !s.is_empty() && s.chars().all(is_safe) {
+        return s.to_string();
+    }
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Writes the manifest of a quarantine batch with the entries moved into it,
+/// or removes the batch directory when nothing was.
+fn write_quarantine_manifest(
+    dir: &Path,
+    root: &Path,
+    removed: &HashMap<PathBuf, u64>,
+) -> Result<(), ErrorBox> {
+    if removed.is_empty() {
+        quarantine::remove_manifest(dir)?;
+        fs::remove_dir(dir)?;
+        return Ok(());
+    }
+    let mut entries: Vec<quarantine::ManifestEntry> = removed
+        .iter()
+        .map(|(path, size)| quarantine::ManifestEntry {
+            original: path.clone(),
+            stored: quarantine::stored_path(path, root),
+            size: *size,
+        })
+        .collect();
+    entries.sort_by(|a, b| a.original.cmp(&b.original));
+    let manifest = quarantine::Manifest {
+        created: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+        root: root.to_path_buf(),
+        entries,
+    };
+    quarantine::write_manifest(dir, &manifest)
+}
+
+#[derive(Clone)]
+pub struct PathEntry {
+    pub pathbuf: PathBuf,
+    pub is_file: bool,
+    pub is_dir: bool,
+    /// Set for FIFOs, sockets and devices, see `special_kind`.
+    pub special: Option<&'static str>,
+    /// Zero for special files.
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub permissions: Option<Permissions>,
+    pub filesystem: Option<u64>,
+    /// Why the last attempt to delete the entry failed.
+    pub error: Option<String>,
+    /// A directory heading of the tree view rather than a result, it's
+    /// never deleted itself.
+    pub group: bool,
+    _is_delete: bool,
+}
+
+impl PathEntry {
+    pub fn new(pathbuf: PathBuf) -> Self {
+        let metadata = fs::metadata(&pathbuf).ok();
+        let special = metadata.as_ref().and_then(|m| special_kind(&m.file_type()));
+        let size = match (&metadata, special) {
+            (Some(m), None) => m.len(),
+            _ => 0,
+        };
+
+        PathEntry {
+            is_file: pathbuf.is_file(),
+            is_dir: metadata.as_ref().is_some_and(|m| m.is_dir()),
+            special,
+            size,
+            modified: metadata.as_ref().and_then(|m| m.modified().ok()),
+            permissions: metadata.map(|m| m.permissions()),
+            filesystem: filesystem_id(&pathbuf),
+            error: None,
+            group: false,
+            pathbuf,
+            _is_delete: true,
+        }
+    }
+
+    /// A tree view heading for the results in `pathbuf`.
+    pub fn group(pathbuf: PathBuf) -> Self {
+        PathEntry {
+            group: true,
+            _is_delete: false,
+            ..PathEntry::new(pathbuf)
+        }
+    }
+
+    pub fn toggle_delete(&mut self) {
+        self._is_delete = !self._is_delete;
+    }
+
+    pub fn set_delete(&mut self, is_delete: bool) {
+        self._is_delete = is_delete;
+    }
+
+    /// The entry size, including everything below it for directories.
+    pub fn disk_size(&self) -> u64 {
+        if self.is_dir {
+            dir_stats(&self.pathbuf).1
+        } else {
+            self.size
+        }
+    }
+
+    /// Moves the entry to the trash, or deletes it for good when `permanent`.
+    pub fn remove(&self, permanent: bool) -> io::Result<()> {
+        if !permanent {
+            return trash::delete(&self.pathbuf).map_err(io::Error::other);
+        }
+        if self.is_dir {
+            fs::remove_dir_all(&self.pathbuf)
+        } else {
+            fs::remove_file(&self.pathbuf)
+        }
+    }
+
+    pub fn is_delete(&self) -> bool {
+        self._is_delete && !self.group
     }
 }
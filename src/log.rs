@@ -0,0 +1,116 @@
+use crate::{config::Config, ErrorBox};
+use serde::{Serialize, Serializer};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+const LOG_FILE: &str = "deletions.jsonl";
+/// The previous log, replaced on each rotation.
+const ROTATED_LOG_FILE: &str = "deletions.jsonl.1";
+/// What the last panic left behind, replaced by the next one.
+const CRASH_FILE: &str = "crash.log";
+/// Where exported deletion summaries go, one file each.
+const REPORT_DIR: &str = "reports";
+
+/// What became of an entry a deletion went through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Trashed,
+    Quarantined,
+    Deleted,
+    Failed,
+}
+
+/// One line of the audit log.
+#[derive(Debug, Serialize)]
+pub struct AuditRecord {
+    /// RFC 3339 timestamp in UTC.
+    pub time: String,
+    /// Written with invalid UTF-8 replaced, JSON holds text only.
+    #[serde(serialize_with = "lossy_path")]
+    pub path: PathBuf,
+    /// Bytes of regular files freed, unknown when the deletion failed.
+    pub size: Option<u64>,
+    pub outcome: Outcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditRecord {
+    pub fn new(time: SystemTime, path: PathBuf, size: Option<u64>, outcome: Outcome) -> Self {
+        AuditRecord {
+            time: humantime::format_rfc3339_seconds(time).to_string(),
+            path,
+            size,
+            outcome,
+            error: None,
+        }
+    }
+
+    /// The record as a line of JSON, without the line break.
+    pub fn to_json(&self) -> Result<String, ErrorBox> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+fn lossy_path<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&path.to_string_lossy())
+}
+
+/// Appends `records` to the audit log in the config directory, one JSON
+/// object per line, first rotating the log when it's bigger than `max_size`.
+pub fn append(records: &[AuditRecord], max_size: u64) -> Result<(), ErrorBox> {
+    let dir = Config::dir().ok_or("no config directory on this platform")?;
+    fs::create_dir_all(&dir)?;
+
+    let path = dir.join(LOG_FILE);
+    if fs::metadata(&path).is_ok_and(|m| m.len() > max_size) {
+        fs::rename(&path, dir.join(ROTATED_LOG_FILE))?;
+    }
+
+    let mut lines = String::new();
+    // a record that can't be written takes no others with it
+    for line in records.iter().filter_map(|record| record.to_json().ok()) {
+        lines.push_str(&line);
+        lines.push('\n');
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// Writes `report` with the time to the crash log in the config directory,
+/// and returns where it went.
+pub fn write_crash(report: &str) -> Result<PathBuf, ErrorBox> {
+    let dir = Config::dir().ok_or("no config directory on this platform")?;
+    fs::create_dir_all(&dir)?;
+
+    let path = dir.join(CRASH_FILE);
+    let time = humantime::format_rfc3339_seconds(SystemTime::now());
+    fs::write(&path, format!("{} {}\n", time, report))?;
+    Ok(path)
+}
+
+/// Writes a deletion summary to a new file of the reports directory in the
+/// config directory, named after the time, and returns where it went.
+pub fn write_report(lines: &[String]) -> Result<PathBuf, ErrorBox> {
+    let dir = Config::dir()
+        .ok_or("no config directory on this platform")?
+        .join(REPORT_DIR);
+    fs::create_dir_all(&dir)?;
+
+    let time = SystemTime::now();
+    let secs = time.duration_since(SystemTime::UNIX_EPOCH)?.as_secs();
+    let path = dir.join(format!("deletion-{}.txt", secs));
+    let mut content = format!("{}\n", humantime::format_rfc3339_seconds(time));
+    for line in lines {
+        content.push_str(line);
+        content.push('\n');
+    }
+    fs::write(&path, content)?;
+    Ok(path)
+}
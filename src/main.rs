@@ -1,45 +1,135 @@
-use eradicate_tui::{App, AppMode, ErrorBox};
+mod cli;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::Parser;
+use cli::Args;
+use eradicate_core::{
+    config::Config,
+    event::{self, Events},
+    export,
+    history::History,
+    humanize, log,
+    open::{self, Opener},
+    presets, ui, App, ErrorBox, PathEntry,
+};
 
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    cursor,
+    event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use std::{
-    io,
-    time::{Duration, Instant},
+    backtrace::Backtrace,
+    fs,
+    io::{self, IsTerminal, Read, Stdout, Write},
+    panic,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
 };
 use tui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Corner, Direction, Layout},
-    style::{Color, Modifier, Style},
-    text::{Span, Spans, Text},
-    widgets::{Block, BorderType, Borders, List, ListItem, Paragraph},
-    Frame, Terminal,
+    Terminal,
 };
-use unicode_width::UnicodeWidthStr;
+
+/// How often a headless run checks on the workers.
+const HEADLESS_POLL: Duration = Duration::from_millis(50);
 
 fn main() -> Result<(), ErrorBox> {
-    // setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-    let tick_rate = Duration::from_millis(250);
-
-    let mut app = App::new();
-    let res = run_app(&mut terminal, &mut app, tick_rate);
-
-    // restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    let args = Args::parse();
+    let config = Config::load()?;
+
+    let mut app = App::with_config(config);
+    app.history = History::load();
+    app.dry_run = args.dry_run;
+    app.force_permanent = args.permanent;
+    app.force_quarantine = args.quarantine;
+    app.dry_run_report = args.report;
+    app.skip_log = args.no_log;
+    // a scripted run deletes, reports or exports every match, not the
+    // first batch
+    if args.yes || args.json || args.export.is_some() {
+        app.config.max_results = 0;
+    }
+    if args.yes {
+        // the summary reads the failures off the list the deletion left
+        app.config.refresh_after_delete = false;
+    }
+    if let Some(root) = args.root {
+        if !root.is_dir() {
+            return Err(format!("not a directory: {}", root.display()).into());
+        }
+        app.set_root(root)?;
+        // the header already shows the root
+        app.status = None;
+    }
+    if let Some(pattern) = args.pattern {
+        app.pattern.content = pattern;
+        app.set_pattern()?;
+    }
+    if let Some(file) = args.import {
+        let text = fs::read_to_string(&file)
+            .map_err(|err| format!("could not read {}: {}", file.display(), err))?;
+        app.load_paths(export::parse_paths(&text));
+        app.status = None;
+    }
+    if args.stdin {
+        // keys are read from the terminal itself then, not from stdin
+        if io::stdin().is_terminal() {
+            return Err("--stdin reads paths piped in, like `fd | eradicate-tui --stdin`".into());
+        }
+        if terminal_input().is_err() && !args.yes && !args.json && args.export.is_none() {
+            return Err("--stdin needs a terminal to read keys from".into());
+        }
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        app.load_paths(export::parse_paths(&text));
+        app.status = None;
+    }
+    if let Some(name) = args.profile {
+        let preset = presets::find(&app.config, &name)
+            .ok_or_else(|| format!("no preset called '{}'", name))?;
+        app.apply_preset(preset)?;
+        // keep a root error for the headless run, drop the review hint
+        if app.prompt.is_none() {
+            app.status = None;
+        }
+    }
 
+    let interrupted = event::interrupt_flag()?;
+    if let Some(dest) = args.export {
+        if wait_until(&mut app, &interrupted, App::is_searching) {
+            return Err("interrupted before exporting anything".into());
+        }
+        return run_export(&mut app, &dest);
+    }
+    if args.json {
+        if wait_until(&mut app, &interrupted, App::is_searching) {
+            return Err("interrupted before the dry run".into());
+        }
+        return run_dry_run_json(&mut app);
+    }
+    if args.yes {
+        if wait_until(&mut app, &interrupted, App::is_searching) {
+            return Err("interrupted before deleting anything".into());
+        }
+        return run_headless(&mut app, &interrupted);
+    }
+
+    install_panic_hook();
+    let mut guard = TerminalGuard::new()?;
+    let tick_rate = Duration::from_millis(app.config.tick_rate);
+
+    let res = run_app(&mut guard.terminal, &mut app, tick_rate, interrupted);
+
+    // restore the terminal before printing anything
+    drop(guard);
+    app.exit_report.iter().for_each(|line| println!("{}", line));
     if let Err(err) = res {
         println!("{:?}", err)
     }
@@ -47,252 +137,270 @@ fn main() -> Result<(), ErrorBox> {
     Ok(())
 }
 
-fn run_app<B: Backend>(
-    terminal: &mut Terminal<B>,
-    app: &mut App,
-    tick_rate: Duration,
-) -> Result<(), ErrorBox> {
-    let mut last_tick = Instant::now();
-
-    loop {
-        terminal.draw(|f| draw_ui(f, app))?;
-
-        let timeout = tick_rate
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
-        if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                match app.app_mode {
-                    AppMode::Normal => match key.code {
-                        KeyCode::Enter => app.toggle_delete(),
-                        KeyCode::Down | KeyCode::Char('j') => app.list.next(),
-                        KeyCode::Up | KeyCode::Char('k') => app.list.previous(),
-                        KeyCode::Char('g') => app.toggle_case_sensitive(),
-                        KeyCode::Char('q') => break,
-                        KeyCode::Char('i') => {
-                            app.set_app_mode(AppMode::Insert);
-                        }
-                        KeyCode::Char('d') => app.delete_active_entries()?,
-                        _ => {}
-                    },
-                    AppMode::Insert => match key.code {
-                        KeyCode::Char(ch) => app.push_ch(ch),
-                        KeyCode::Enter => {
-                            app.set_pattern()?;
-                            app.set_app_mode(AppMode::Normal);
-                        }
-                        KeyCode::Backspace => app.pop_ch(),
-                        KeyCode::Esc => {
-                            app.set_app_mode(AppMode::Normal);
-                        }
-                        _ => {}
-                    },
-                }
-            }
-        }
+/// The terminal in raw mode on the alternate screen, put back as it was when
+/// dropped, so an error returned from the event loop leaves a usable shell.
+struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
 
-        if last_tick.elapsed() >= tick_rate {
-            last_tick = Instant::now();
-        }
+impl TerminalGuard {
+    fn new() -> Result<Self, ErrorBox> {
+        enter_terminal()?;
+        let terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        Ok(TerminalGuard { terminal })
     }
+}
 
+/// Enters raw mode and the alternate screen, capturing the mouse.
+fn enter_terminal() -> Result<(), ErrorBox> {
+    enable_raw_mode()?;
+    if let Err(err) = execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture) {
+        restore_terminal();
+        return Err(err.into());
+    }
     Ok(())
 }
 
-fn draw_ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
-    let main_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(f.size());
-
-    let bg_box = Block::default().style(Style::default().bg(Color::Black));
-    f.render_widget(bg_box, f.size());
-    let left_area = main_chunks[0];
-    let right_area = main_chunks[1];
-
-    // build left side
-    let left_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints(
-            [
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Max(10),
-                Constraint::Max(10),
-            ]
-            .as_ref(),
-        )
-        .split(left_area);
-
-    // build help message
-
-    let (msg, style) = match app.app_mode {
-        AppMode::Normal => (
-            vec![
-                Span::styled("[i]", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw("nsert mode"),
-                Span::raw(", "),
-                Span::styled("[g]", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" toogle case sensitive matches, "),
-                Span::styled("[q]", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw("uit"),
-            ],
-            Style::default(),
-        ),
-        AppMode::Insert => (
-            vec![
-                Span::styled("[Enter]", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" set the pattern, "),
-                Span::styled("[Esc]", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" exit insert mode"),
-            ],
-            Style::default(),
-        ),
-    };
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
 
-    let mut text = Text::from(Spans::from(msg));
-    text.patch_style(style);
-    let help_message = Paragraph::new(text);
-
-    f.render_widget(help_message, left_chunks[0]);
-
-    // display current pattern
-
-    let case_text = if app.is_case_sensitive() { "ON" } else { "OFF" };
-
-    let spans = match app.pattern.content.is_empty() {
-        false => Spans::from(vec![
-            Span::raw("Searching: "),
-            Span::styled(
-                app.pattern.content.as_str(),
-                Style::default().add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(", case sensitive: "),
-            Span::styled(case_text, Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw(", "),
-        ]),
-        true => Spans::from(vec![Span::styled(
-            "Empty pattern, try inserting a new one",
-            Style::default().add_modifier(Modifier::ITALIC),
-        )]),
-    };
+/// Leaves raw mode and the alternate screen. Safe to call more than once.
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        cursor::Show
+    );
+}
 
-    let mut text = Text::from(spans);
-    text.patch_style(Style::default().fg(Color::Magenta));
-    f.render_widget(Paragraph::new(text), left_chunks[1]);
+/// Restores the terminal before a panic on the main thread is reported,
+/// it would be lost on the alternate screen otherwise, and keeps the report
+/// with a backtrace in the crash log.
+fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        // workers panicking leave the interface running
+        if thread::current().name() == Some("main") {
+            restore_terminal();
+        }
+        default_hook(info);
+        let report = format!("{}\n\n{}", info, Backtrace::force_capture());
+        match log::write_crash(&report) {
+            Ok(path) => eprintln!("crash log written to {}", path.display()),
+            Err(err) => eprintln!("could not write the crash log: {}", err),
+        }
+    }));
+}
 
-    // display input
+/// Deletes every match of the startup pattern, for scripted use.
+fn run_headless(app: &mut App, interrupted: &AtomicBool) -> Result<(), ErrorBox> {
+    if let Some(err) = app.pattern_error.take() {
+        return Err(format!("invalid pattern: {}", err).into());
+    }
+    if let Some(status) = app.status.take() {
+        return Err(status.into());
+    }
 
-    let (name, content) = (&app.pattern.name, &app.pattern.content);
-    let style = match app.app_mode {
-        AppMode::Insert => app.pattern.active_style,
-        AppMode::Normal => app.pattern.normal_style,
-    };
-    
-    let pattern_input = create_input(name, content, style);
-    f.render_widget(pattern_input, left_chunks[2]);
-
-    let active_area = left_chunks[2];
-
-    match app.app_mode {
-        AppMode::Normal => {}
-        AppMode::Insert => f.set_cursor(
-            active_area.x + app.pattern.content.width() as u16 + 1,
-            active_area.y + 1,
-        ),
-    }
-
-    // end build left side
-
-    // build right side
-    let right_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints([Constraint::Percentage(5), Constraint::Percentage(95)].as_ref())
-        .split(right_area);
-
-    let spans = Spans::from(vec![
-        Span::styled("[Enter]", Style::default().add_modifier(Modifier::BOLD)),
-        Span::raw(" toggle entry deletion, "),
-        Span::styled("[d]", Style::default().add_modifier(Modifier::BOLD)),
-        Span::raw("elete active entries"),
-    ]);
-
-    let help_style = match app.app_mode {
-        AppMode::Normal => Style::default(),
-        AppMode::Insert => Style::default().fg(Color::Gray),
-    };
+    if app.dry_run {
+        app.delete_active_entries(false)?;
+        if let Some(popup) = app.popup.take() {
+            popup.lines.iter().for_each(|line| println!("{}", line));
+        }
+        return match app.status.take() {
+            Some(status) => Err(status.into()),
+            None => Ok(()),
+        };
+    }
 
-    let mut text = Text::from(spans);
-    text.patch_style(help_style);
-    let help_text = Paragraph::new(text);
-    f.render_widget(help_text, right_chunks[0]);
+    let entries = app.entries_to_delete();
+    if entries.is_empty() {
+        println!("Nothing matched {}", app.pattern.content);
+        return Ok(());
+    }
 
-    let chunk_width = right_area.width as usize;
+    let sizes: Vec<u64> = entries.iter().map(PathEntry::disk_size).collect();
+    app.delete_active_entries(app.permanent_delete())?;
+    let mut stopped = wait_until(app, interrupted, App::is_deleting);
+    // --yes answers every per directory confirmation too
+    while app.confirm.is_some() && !stopped {
+        app.accept_confirm()?;
+        stopped = wait_until(app, interrupted, App::is_deleting);
+    }
+    if stopped {
+        app.exit_report.iter().for_each(|line| println!("{}", line));
+        return Err("interrupted".into());
+    }
 
-    let items: Vec<ListItem> = app
+    let failed: Vec<&PathEntry> = app
         .list
         .items
         .iter()
-        .map(|entry| {
-            let (turbo, turbo_color) = match entry.is_delete() {
-                true => ("o <> o", Color::Red),
-                false => ("- <> -", Color::Gray),
-            };
-
-            let file_type = if entry.is_file { "File" } else { "Dir" };
-
-            let header = Spans::from(vec![
-                Span::styled(file_type, Style::default().fg(Color::LightGreen)),
-                Span::raw(" "),
-                Span::styled(turbo, Style::default().fg(turbo_color)),
-            ]);
-
-            let path_display = entry.pathbuf.display().to_string();
-            let path_desc = Spans::from(vec![Span::raw(path_display)]);
-
-            ListItem::new(vec![
-                header,
-                path_desc,
-                Spans::from("-".repeat(chunk_width)),
-            ])
-            .style(Style::default().fg(Color::LightCyan).bg(Color::Black))
-        })
+        .filter(|e| e.error.is_some())
         .collect();
+    let mut removed = 0;
+    let mut freed = 0;
+    for (entry, size) in entries.iter().zip(sizes) {
+        if failed.iter().any(|e| e.pathbuf == entry.pathbuf) {
+            continue;
+        }
+        println!("{}", app.display_path(entry).display());
+        removed += 1;
+        freed += size;
+    }
+    for entry in failed.iter() {
+        eprintln!(
+            "{}: {}",
+            app.display_path(entry).display(),
+            entry.error.as_deref().unwrap_or_default()
+        );
+    }
+
+    let destination = match (app.permanent_delete(), app.quarantine()) {
+        (true, _) => "",
+        (false, true) => ", moved to the quarantine",
+        (false, false) => ", moved to the trash",
+    };
+    println!(
+        "Eradicated {} entries, {}{}",
+        removed,
+        humanize::size(freed),
+        destination
+    );
+    if !failed.is_empty() {
+        return Err(format!("{} entries could not be deleted", failed.len()).into());
+    }
+
+    Ok(())
+}
+
+/// Writes every match to `dest`, or to stdout when it's `-`, for scripted use.
+fn run_export(app: &mut App, dest: &Path) -> Result<(), ErrorBox> {
+    if let Some(err) = app.pattern_error.take() {
+        return Err(format!("invalid pattern: {}", err).into());
+    }
+    if let Some(status) = app.status.take() {
+        return Err(status.into());
+    }
+
+    let entries = app.exported_entries(false);
+    if dest == Path::new("-") {
+        print!("{}", export::render(&entries, export::Format::Plain)?);
+        return Ok(());
+    }
+    fs::write(
+        dest,
+        export::render(&entries, export::Format::from_path(dest))?,
+    )?;
+    Ok(())
+}
+
+/// Prints what deleting every match would remove as JSON, also writing it to
+/// the report file if set, for scripts and CI jobs.
+fn run_dry_run_json(app: &mut App) -> Result<(), ErrorBox> {
+    if let Some(err) = app.pattern_error.take() {
+        return Err(format!("invalid pattern: {}", err).into());
+    }
+    if let Some(status) = app.status.take() {
+        return Err(status.into());
+    }
+
+    let text = app.dry_run_document().to_json()?;
+    if let Some(path) = &app.dry_run_report {
+        fs::write(path, &text)
+            .map_err(|err| format!("could not write {}: {}", path.display(), err))?;
+    }
+    print!("{}", text);
+    Ok(())
+}
 
-    let n = app.get_entries_by(|e| e.is_delete()).len();
-    let spans = Spans::from(vec![
-        Span::raw("Entries to eradicate: "),
-        Span::styled(
-            n.to_string(),
-            Style::default().add_modifier(Modifier::BOLD).fg(Color::Red),
-        ),
-        Span::raw(" "),
-    ]);
-
-    let list = List::new(items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(spans.0)
-                .border_type(BorderType::Rounded),
-        )
-        .highlight_style(
-            Style::default()
-                .bg(Color::DarkGray)
-                .add_modifier(Modifier::ITALIC),
-        )
-        .start_corner(Corner::TopLeft);
-
-    f.render_stateful_widget(list, right_chunks[1], &mut app.list.state);
+/// Polls the workers while `busy` holds, for use without an event loop.
+/// SIGINT or SIGTERM stop them cleanly, then it returns true.
+fn wait_until(app: &mut App, interrupted: &AtomicBool, busy: fn(&App) -> bool) -> bool {
+    while busy(app) {
+        if interrupted.load(Ordering::Relaxed) {
+            app.interrupt();
+            return true;
+        }
+        app.poll_search();
+        app.poll_delete();
+        thread::sleep(HEADLESS_POLL);
+    }
+    false
+}
+
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    tick_rate: Duration,
+    interrupted: Arc<AtomicBool>,
+) -> Result<(), ErrorBox> {
+    let mut events = Events::new(tick_rate, Arc::clone(&interrupted));
+
+    app.update_watcher();
+    terminal.draw(|f| ui::draw(f, app))?;
+    while !app.should_quit {
+        let event = events.recv()?;
+        if app.handle_event(event)? {
+            terminal.draw(|f| ui::draw(f, app))?;
+        }
+        if let Some(text) = app.clipboard.take() {
+            copy_to_clipboard(&text)?;
+        }
+        if let Some((path, opener)) = app.open_request.take() {
+            // the input thread would steal keys from the opened program
+            drop(events);
+            app.status = open_outside(&path, opener)?;
+            events = Events::new(tick_rate, Arc::clone(&interrupted));
+            terminal.clear()?;
+            terminal.draw(|f| ui::draw(f, app))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The keyboard, when stdin is taken by paths piped in.
+#[cfg(unix)]
+fn terminal_input() -> io::Result<fs::File> {
+    fs::File::open("/dev/tty")
 }
 
-fn create_input<'a>(name: &'a str, text: &'a str, style: Style) -> Paragraph<'a> {
-    Paragraph::new(text).style(style).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_type(BorderType::Rounded)
-            .title(name),
-    )
+#[cfg(not(unix))]
+fn terminal_input() -> io::Result<fs::File> {
+    fs::File::open("CONIN$")
+}
+
+/// Puts `text` on the system clipboard with the OSC 52 escape sequence, which
+/// the terminal handles, over SSH too.
+fn copy_to_clipboard(text: &str) -> Result<(), ErrorBox> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Hands the terminal to the program opening `path` until it exits, then
+/// takes it back. Returns what went wrong with the program.
+fn open_outside(path: &Path, opener: Opener) -> Result<Option<String>, ErrorBox> {
+    restore_terminal();
+    let mut command = open::command(opener, path);
+    // stdin may be the paths piped in
+    if !io::stdin().is_terminal() {
+        if let Ok(tty) = terminal_input() {
+            command.stdin(tty);
+        }
+    }
+    let program = command.get_program().to_string_lossy().into_owned();
+    let status = match command.status() {
+        Ok(status) if status.success() => None,
+        Ok(status) => Some(format!("{} exited with {}", program, status)),
+        Err(err) => Some(format!("could not run {}: {}", program, err)),
+    };
+    enter_terminal()?;
+    Ok(status)
 }
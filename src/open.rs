@@ -0,0 +1,49 @@
+use std::{env, path::Path, process::Command};
+
+/// What to open an entry with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Opener {
+    /// `$VISUAL` or `$EDITOR`, `vi` when neither is set.
+    Editor,
+    /// Whatever the desktop opens the file with.
+    System,
+}
+
+/// The command opening `path` with `opener`.
+pub fn command(opener: Opener, path: &Path) -> Command {
+    let mut command = match opener {
+        Opener::Editor => {
+            let editor = env::var("VISUAL")
+                .or_else(|_| env::var("EDITOR"))
+                .ok()
+                .filter(|editor| !editor.trim().is_empty())
+                .unwrap_or_else(|| String::from("vi"));
+            // like `code --wait`
+            let mut words = editor.split_whitespace();
+            let mut command = Command::new(words.next().unwrap_or("vi"));
+            command.args(words);
+            command
+        }
+        Opener::System => system_opener(),
+    };
+    command.arg(path);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn system_opener() -> Command {
+    Command::new("open")
+}
+
+#[cfg(windows)]
+fn system_opener() -> Command {
+    let mut command = Command::new("cmd");
+    // the first quoted argument of start is the window title
+    command.args(["/C", "start", ""]);
+    command
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn system_opener() -> Command {
+    Command::new("xdg-open")
+}
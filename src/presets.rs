@@ -0,0 +1,99 @@
+use crate::{
+    config::{ByteSize, Config},
+    search::MatchEngine,
+};
+use serde::{Deserialize, Serialize};
+
+/// A named pattern for a well-known kind of junk, or a recurring cleanup
+/// from the config, like:
+///
+/// ```toml
+/// [[presets]]
+/// name = "build-junk"
+/// pattern = "**/target **/dist"
+/// excludes = ["**/keep/**"]
+/// min_age = "30d"
+/// min_size = "10M"
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub pattern: String,
+    #[serde(default)]
+    pub engine: MatchEngine,
+    /// Left as it is when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_sensitive: Option<bool>,
+    /// Added to the pattern as `!exclude` words.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excludes: Vec<String>,
+    /// Only entries modified longer ago than this, like `90d`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_age: Option<String>,
+    /// Only entries larger than this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_size: Option<ByteSize>,
+}
+
+impl Preset {
+    pub fn new(name: &str, pattern: &str) -> Self {
+        Preset {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            engine: MatchEngine::Glob,
+            case_sensitive: None,
+            excludes: vec![],
+            min_age: None,
+            min_size: None,
+        }
+    }
+
+    /// The pattern with the excludes and criteria written in, as it's
+    /// searched for.
+    pub fn search_pattern(&self) -> String {
+        let mut words = vec![self.pattern.clone()];
+        words.extend(self.excludes.iter().map(|exclude| format!("!{}", exclude)));
+        if let Some(age) = &self.min_age {
+            words.push(format!("+mtime:{}", age));
+        }
+        if let Some(size) = self.min_size {
+            words.push(format!("size>{}", String::from(size)));
+        }
+        words.join(" ")
+    }
+}
+
+/// Junk left behind by common tools. Nested copies are excluded where the
+/// outer one takes them along anyway.
+pub fn builtin() -> Vec<Preset> {
+    vec![
+        Preset::new("node_modules", "**/node_modules !**/node_modules/**"),
+        Preset::new("Rust target", "**/target !**/target/**"),
+        Preset::new(
+            "Python caches",
+            "**/__pycache__ **/.pytest_cache **/.mypy_cache !**/.venv/**",
+        ),
+        Preset::new(
+            "JS framework caches",
+            "**/.next **/.nuxt **/.parcel-cache !**/node_modules/**",
+        ),
+        Preset::new("OS junk files", "**/.DS_Store **/Thumbs.db **/desktop.ini"),
+        Preset::new("Editor swap files", "**/*.swp **/*.swo **/*~"),
+        Preset::new("Temporary *.tmp", "**/*.tmp"),
+    ]
+}
+
+/// The built-in presets followed by the ones from the config file.
+pub fn all(config: &Config) -> Vec<Preset> {
+    let mut presets = builtin();
+    presets.extend(config.presets.iter().cloned());
+    presets
+}
+
+/// The preset called `name`, config ones shadowing built-in ones.
+pub fn find(config: &Config, name: &str) -> Option<Preset> {
+    all(config)
+        .into_iter()
+        .rev()
+        .find(|preset| preset.name == name)
+}
@@ -0,0 +1,84 @@
+use crate::{humanize, search::BINARY_SNIFF_LEN};
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::Path,
+};
+
+/// Only this many bytes of a file are read for its preview.
+const PREVIEW_READ_LIMIT: u64 = 64 * 1024;
+
+/// Up to `max_lines` lines describing `path`: the first lines of a text
+/// file, or the content of a directory with the item count of each
+/// subdirectory.
+pub fn preview(path: &Path, max_lines: usize) -> Vec<String> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) => return vec![format!("Can't read {}: {}", path.display(), err)],
+    };
+
+    let mut lines = if metadata.is_dir() {
+        dir_lines(path, max_lines)
+    } else if metadata.is_file() {
+        file_lines(path, metadata.len())
+    } else {
+        vec![String::from("No preview for special files")]
+    };
+    lines.truncate(max_lines);
+    lines
+}
+
+fn file_lines(path: &Path, len: u64) -> Vec<String> {
+    let mut bytes = vec![];
+    let read =
+        File::open(path).and_then(|file| file.take(PREVIEW_READ_LIMIT).read_to_end(&mut bytes));
+    if let Err(err) = read {
+        return vec![format!("Can't read {}: {}", path.display(), err)];
+    }
+
+    let sniff_len = bytes.len().min(BINARY_SNIFF_LEN);
+    if bytes[..sniff_len].contains(&0) {
+        return vec![format!("Binary file, {}", humanize::size(len))];
+    }
+    if bytes.is_empty() {
+        return vec![String::from("Empty file")];
+    }
+
+    String::from_utf8_lossy(&bytes)
+        .lines()
+        .map(|line| line.replace('\t', "    "))
+        .collect()
+}
+
+/// Directories first, then files, each sorted by name. Only the listed
+/// subdirectories are counted.
+fn dir_lines(path: &Path, max_lines: usize) -> Vec<String> {
+    let children = match fs::read_dir(path) {
+        Ok(children) => children,
+        Err(err) => return vec![format!("Can't read {}: {}", path.display(), err)],
+    };
+
+    let mut children: Vec<(bool, String)> = children
+        .filter_map(Result::ok)
+        .map(|child| {
+            let is_dir = child.file_type().is_ok_and(|kind| kind.is_dir());
+            (!is_dir, child.file_name().to_string_lossy().into_owned())
+        })
+        .collect();
+    if children.is_empty() {
+        return vec![String::from("Empty directory")];
+    }
+    children.sort();
+
+    let mut lines = vec![format!("{} items", children.len())];
+    lines.extend(children.into_iter().take(max_lines).map(|(is_file, name)| {
+        if is_file {
+            return name;
+        }
+        match fs::read_dir(path.join(&name)) {
+            Ok(items) => format!("{}/ ({} items)", name, items.count()),
+            Err(_) => format!("{}/", name),
+        }
+    }));
+    lines
+}
@@ -0,0 +1,66 @@
+use crate::filesystem_id;
+use std::path::{Component, Path, PathBuf};
+
+/// Why `path` must never be deleted, if it must not. Besides the well-known
+/// places, `extra` lists paths from the config. A directory holding a
+/// protected path is protected too, deleting it would take the path with it.
+/// Paths are compared once resolved, `~/proj/..` is the home directory.
+pub fn protected_reason(path: &Path, extra: &[PathBuf]) -> Option<String> {
+    let path = &resolved(path);
+    if path.parent().is_none() {
+        return Some(String::from("the filesystem root"));
+    }
+    if path.components().any(|c| c.as_os_str() == ".git") {
+        return Some(String::from("git internals"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        if resolved(&home).starts_with(path) {
+            return Some(String::from("the home directory or one holding it"));
+        }
+    }
+    if let Some(listed) = extra
+        .iter()
+        .find(|listed| resolved(listed).starts_with(path))
+    {
+        return Some(format!("protected by the config: {}", listed.display()));
+    }
+    if is_mount_point(path) {
+        return Some(String::from("a mount point"));
+    }
+    None
+}
+
+/// Whether `path` lives on another filesystem than its parent directory.
+fn is_mount_point(path: &Path) -> bool {
+    let parent = match path.parent() {
+        Some(parent) => parent,
+        None => return true,
+    };
+    match (filesystem_id(path), filesystem_id(parent)) {
+        (Some(id), Some(parent_id)) => id != parent_id,
+        _ => false,
+    }
+}
+
+/// `path` with `.` and `..` taken out and the links to its parent directories
+/// followed. A link itself is kept, deleting it leaves what it points to.
+fn resolved(path: &Path) -> PathBuf {
+    let resolved = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => parent.canonicalize().map(|parent| parent.join(name)),
+        _ => path.canonicalize(),
+    };
+    resolved.unwrap_or_else(|_| {
+        // what doesn't exist can only be cleaned up as written
+        let mut clean = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    clean.pop();
+                }
+                _ => clean.push(component),
+            }
+        }
+        clean
+    })
+}
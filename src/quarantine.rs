@@ -0,0 +1,246 @@
+use crate::ErrorBox;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+    time::SystemTime,
+};
+
+const MANIFEST_FILE: &str = "manifest.json";
+/// Where entries from outside the search root go in a batch.
+const OUTSIDE_ROOT: &str = "_outside";
+
+/// Where quarantined batches go, `<data dir>/eradicate/quarantine`.
+pub fn dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("eradicate").join("quarantine"))
+}
+
+/// An entry moved into a batch.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Where the entry was, and goes back to on restore.
+    pub original: PathBuf,
+    /// Where the entry is, relative to the batch directory.
+    pub stored: PathBuf,
+    /// Bytes of regular files in the entry.
+    pub size: u64,
+}
+
+/// What a batch holds, written next to the entries as `manifest.json`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// RFC 3339 timestamp in UTC.
+    pub created: String,
+    /// The search root the stored paths are relative to.
+    pub root: PathBuf,
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    pub fn size(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.size).sum()
+    }
+}
+
+/// The entries of one deletion, kept in a directory of the quarantine.
+#[derive(Clone, Debug)]
+pub struct Batch {
+    pub dir: PathBuf,
+    pub manifest: Manifest,
+}
+
+/// Creates an empty batch directory in `base`, named after `time`.
+pub fn create_batch(base: &Path, time: SystemTime) -> io::Result<PathBuf> {
+    fs::create_dir_all(base)?;
+    // colons are not allowed in file names everywhere
+    let name = humantime::format_rfc3339_seconds(time)
+        .to_string()
+        .replace(':', "-");
+    let mut dir = base.join(&name);
+    let mut n = 1;
+    loop {
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                n += 1;
+                dir = base.join(format!("{}-{}", name, n));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Where `path` goes in a batch: relative to `root` like in the search,
+/// below `_outside` with its full path when it's not in `root`.
+pub fn stored_path(path: &Path, root: &Path) -> PathBuf {
+    match path.strip_prefix(root) {
+        Ok(relative) if relative.components().next().is_some() => relative.to_path_buf(),
+        _ => {
+            let normal = path
+                .components()
+                .filter(|component| matches!(component, Component::Normal(_)));
+            Path::new(OUTSIDE_ROOT).join(normal.collect::<PathBuf>())
+        }
+    }
+}
+
+/// Moves `path` into the batch directory `batch`, at its `stored_path`.
+pub fn stash(path: &Path, batch: &Path, root: &Path) -> io::Result<()> {
+    move_path(path, &batch.join(stored_path(path, root)))
+}
+
+/// Writes the manifest of the batch in `dir`.
+pub fn write_manifest(dir: &Path, manifest: &Manifest) -> Result<(), ErrorBox> {
+    let content = serde_json::to_string_pretty(manifest)?;
+    fs::write(dir.join(MANIFEST_FILE), content)?;
+    Ok(())
+}
+
+/// Removes the manifest of the batch in `dir`, if it has one.
+pub fn remove_manifest(dir: &Path) -> io::Result<()> {
+    match fs::remove_file(dir.join(MANIFEST_FILE)) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Every batch in `base`, newest first. Directories without a readable
+/// manifest are left out.
+pub fn list(base: &Path) -> Vec<Batch> {
+    let dirs = match fs::read_dir(base) {
+        Ok(dirs) => dirs,
+        Err(_) => return vec![],
+    };
+    let mut batches: Vec<Batch> = dirs
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let dir = entry.path();
+            let content = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+            let manifest = serde_json::from_str(&content).ok()?;
+            Some(Batch { dir, manifest })
+        })
+        .collect();
+    batches.sort_by(|a, b| b.manifest.created.cmp(&a.manifest.created));
+    batches
+}
+
+/// Moves the entries of `batch` back where they were, and removes the batch
+/// once it's empty. Entries that can't go back, like when their path is taken
+/// again, stay in the batch. Entries never moved in, when the deletion was cut
+/// short, are skipped. Returns how many were restored.
+pub fn restore(batch: &Batch) -> Result<usize, ErrorBox> {
+    let mut left = vec![];
+    let mut first_error = None;
+    // parents first, so a restored child can't block its parent
+    let mut entries = batch.manifest.entries.clone();
+    entries.retain(|entry| batch.dir.join(&entry.stored).symlink_metadata().is_ok());
+    entries.sort_by_key(|entry| entry.original.components().count());
+    for entry in entries.iter() {
+        let result = match entry.original.symlink_metadata() {
+            Ok(_) => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "the original path is taken",
+            )),
+            Err(_) => move_path(&batch.dir.join(&entry.stored), &entry.original),
+        };
+        if let Err(err) = result {
+            first_error.get_or_insert(format!("{}: {}", entry.original.display(), err));
+            left.push(entry.clone());
+        }
+    }
+
+    let restored = entries.len() - left.len();
+    match first_error {
+        None => {
+            fs::remove_dir_all(&batch.dir)?;
+            Ok(restored)
+        }
+        Some(err) => {
+            let manifest = Manifest {
+                entries: left,
+                ..batch.manifest.clone()
+            };
+            write_manifest(&batch.dir, &manifest)?;
+            Err(format!(
+                "restored {} of {} entries, {}",
+                restored,
+                entries.len(),
+                err
+            )
+            .into())
+        }
+    }
+}
+
+/// Deletes the batch and everything in it for good.
+pub fn purge(batch: &Batch) -> io::Result<()> {
+    fs::remove_dir_all(&batch.dir)
+}
+
+/// Renames `from` to `to`, creating the parents of `to`. Across filesystems,
+/// where renaming is not possible, `from` is copied and then deleted.
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    if to.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", to.display()),
+        ));
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match fs::rename(from, to) {
+        Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+            if let Err(err) = copy_all(from, to) {
+                // what got copied so far, the original is still there
+                let _ = match to.symlink_metadata().map(|m| m.is_dir()) {
+                    Ok(true) => fs::remove_dir_all(to),
+                    _ => fs::remove_file(to),
+                };
+                return Err(err);
+            }
+            match from.symlink_metadata()?.is_dir() {
+                true => fs::remove_dir_all(from),
+                false => fs::remove_file(from),
+            }
+        }
+        result => result,
+    }
+}
+
+/// Copies `from` to `to`, directories with everything in them. Symlinks are
+/// copied as links, not followed. FIFOs, sockets and devices are not copied,
+/// reading them could block or never end.
+fn copy_all(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = from.symlink_metadata()?;
+    if metadata.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_all(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, metadata.permissions())
+    } else if metadata.file_type().is_symlink() {
+        copy_link(from, to)
+    } else if metadata.is_file() {
+        fs::copy(from, to).map(|_| ())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("can't move the special file {}", from.display()),
+        ))
+    }
+}
+
+#[cfg(unix)]
+fn copy_link(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_link(from: &Path, _to: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("can't copy the link {}", from.display()),
+    ))
+}
@@ -0,0 +1,611 @@
+use crate::{filter::Criterion, ErrorBox, PathEntry, PatternError};
+use globset::{ErrorKind, GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
+use ignore::{WalkBuilder, WalkState};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::Read,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+};
+use walkdir::WalkDir;
+
+/// Only this many bytes of each file are searched by the content engine.
+const CONTENT_READ_LIMIT: u64 = 4 * 1024 * 1024;
+/// A NUL byte within this many leading bytes marks a file as binary.
+pub(crate) const BINARY_SNIFF_LEN: usize = 8 * 1024;
+/// Lists paths to skip like a `.gitignore`, without affecting git.
+pub const IGNORE_FILE: &str = ".eradicateignore";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchEngine {
+    /// Match paths against the pattern as a glob.
+    #[default]
+    Glob,
+    /// Match full paths against the pattern as a regex.
+    Regex,
+    /// Match text files containing the pattern.
+    Content,
+}
+
+impl MatchEngine {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MatchEngine::Glob => "glob",
+            MatchEngine::Regex => "regex",
+            MatchEngine::Content => "content",
+        }
+    }
+
+    pub fn next(&self) -> MatchEngine {
+        match self {
+            MatchEngine::Glob => MatchEngine::Regex,
+            MatchEngine::Regex => MatchEngine::Content,
+            MatchEngine::Content => MatchEngine::Glob,
+        }
+    }
+}
+
+/// Glob patterns compiled into one set, matched while walking the
+/// directories they start from once.
+pub struct Globs {
+    set: GlobSet,
+    /// Directories to walk, none inside another, with how deep to go.
+    bases: Vec<(PathBuf, Option<usize>)>,
+}
+
+impl Globs {
+    /// Like with a shell, `*` and `?` never match a path separator, `**`
+    /// matches any number of directories.
+    pub fn new(patterns: &[String], case_sensitive: bool) -> Result<Self, ErrorBox> {
+        let set = compile(patterns, case_sensitive, true)?;
+
+        let mut bases: Vec<(PathBuf, Option<usize>)> = patterns
+            .iter()
+            .map(|pattern| {
+                let base = literal_prefix(pattern);
+                let depth = match pattern.contains("**") {
+                    true => None,
+                    false => {
+                        Some(Path::new(pattern).components().count() - base.components().count())
+                    }
+                };
+                (base, depth)
+            })
+            .collect();
+        // a base inside another one is walked as part of it, deep enough to
+        // reach what its own patterns match
+        bases.sort();
+        let mut merged: Vec<(PathBuf, Option<usize>)> = vec![];
+        for (base, depth) in bases {
+            match merged.last_mut() {
+                Some((outer, outer_depth)) if base.starts_with(&*outer) => {
+                    let extra = base.components().count() - outer.components().count();
+                    *outer_depth = match (*outer_depth, depth) {
+                        (Some(a), Some(b)) => Some(a.max(b + extra)),
+                        _ => None,
+                    };
+                }
+                _ => merged.push((base, depth)),
+            }
+        }
+
+        Ok(Globs { set, bases: merged })
+    }
+
+    /// Paths matching any of the patterns. With `respect_ignore` whatever
+    /// ignore files list is skipped, see `walk_paths`.
+    pub fn paths(&self, respect_ignore: bool) -> impl Iterator<Item = PathBuf> + '_ {
+        self.bases
+            .iter()
+            .flat_map(move |(base, depth)| walk_paths(base, respect_ignore, *depth))
+            .map(|(path, _)| path)
+            .filter(|path| self.set.is_match(path))
+    }
+
+    /// Calls `found` with each path matching any of the patterns, from
+    /// `threads` threads, see `walk_each`. Stops once `found` returns false.
+    pub fn for_each_path<F>(&self, respect_ignore: bool, threads: usize, found: F)
+    where
+        F: Fn(PathBuf) -> bool + Sync,
+    {
+        for (base, depth) in &self.bases {
+            let done = walk_each(base, respect_ignore, *depth, threads, |path, _| {
+                !self.set.is_match(&path) || found(path)
+            });
+            if !done {
+                return;
+            }
+        }
+    }
+}
+
+/// Compiles `patterns` into one set. Unless `literal_separator`, `*` and `?`
+/// match path separators too.
+pub fn compile(
+    patterns: &[String],
+    case_sensitive: bool,
+    literal_separator: bool,
+) -> Result<GlobSet, ErrorBox> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(literal_separator)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .map_err(|err| format!("invalid pattern '{}': {}", pattern, err.kind()))?;
+        builder.add(glob);
+    }
+    Ok(builder.build()?)
+}
+
+/// Finds the parts of a path a search matched, to show why it was listed.
+pub enum Highlighter {
+    /// Each glob with its components, `None` standing for `**`.
+    Glob(Vec<(GlobMatcher, Vec<Option<GlobMatcher>>)>),
+    Regex(Regex),
+}
+
+impl Highlighter {
+    /// Like `Globs::new`. A pattern with a separator inside braces can't be
+    /// taken apart, nothing it matches is highlighted.
+    pub fn glob(patterns: &[String], case_sensitive: bool) -> Self {
+        let build = |glob: &str| {
+            GlobBuilder::new(glob)
+                .literal_separator(true)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .ok()
+                .map(|glob| glob.compile_matcher())
+        };
+        let globs = patterns
+            .iter()
+            .filter_map(|pattern| {
+                let components = pattern
+                    .split('/')
+                    .map(|component| match component {
+                        "**" => Some(None),
+                        _ => build(component).map(Some),
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some((build(pattern)?, components))
+            })
+            .collect();
+        Highlighter::Glob(globs)
+    }
+
+    /// Byte ranges of `path` that were matched, in order and apart. A glob
+    /// matches whole components: those before its first `**` are lined up
+    /// with the start of the path, those after its last one with the end.
+    pub fn spans(&self, path: &str) -> Vec<Range<usize>> {
+        let mut spans: Vec<Range<usize>> = match self {
+            Highlighter::Regex(regex) => regex
+                .find_iter(path)
+                .map(|found| found.range())
+                .filter(|span| !span.is_empty())
+                .collect(),
+            Highlighter::Glob(globs) => {
+                let mut parts = vec![];
+                let mut start = 0;
+                for (i, ch) in path.char_indices() {
+                    if ch == '/' {
+                        parts.push(start..i);
+                        start = i + 1;
+                    }
+                }
+                parts.push(start..path.len());
+
+                globs
+                    .iter()
+                    .filter(|(glob, _)| glob.is_match(path))
+                    .flat_map(|(_, components)| lined_up(components, &parts))
+                    .filter(|(component, part)| {
+                        !part.is_empty() && component.is_match(&path[part.clone()])
+                    })
+                    .map(|(_, part)| part)
+                    .collect()
+            }
+        };
+
+        // several patterns can match the same parts
+        spans.sort_by_key(|span| span.start);
+        let mut merged: Vec<Range<usize>> = vec![];
+        for span in spans {
+            match merged.last_mut() {
+                Some(last) if span.start <= last.end => last.end = last.end.max(span.end),
+                _ => merged.push(span),
+            }
+        }
+        merged
+    }
+}
+
+/// Pairs the components of a glob with the `parts` of a path it matched,
+/// leaving out the `**` ones and what they stand for.
+fn lined_up<'a>(
+    components: &'a [Option<GlobMatcher>],
+    parts: &[Range<usize>],
+) -> Vec<(&'a GlobMatcher, Range<usize>)> {
+    let head = components.iter().take_while(|c| c.is_some()).count();
+    let tail = match head == components.len() {
+        true => 0,
+        false => components.iter().rev().take_while(|c| c.is_some()).count(),
+    };
+    components[..head]
+        .iter()
+        .zip(parts)
+        .chain(
+            components[components.len() - tail..]
+                .iter()
+                .zip(&parts[parts.len().saturating_sub(tail)..]),
+        )
+        .filter_map(|(component, part)| Some((component.as_ref()?, part.clone())))
+        .collect()
+}
+
+/// Parses a glob as typed, pointing at the part of it at fault when it's
+/// invalid.
+pub fn check_glob(pattern: &str) -> Result<(), PatternError> {
+    let err = match GlobBuilder::new(pattern).build() {
+        Ok(_) => return Ok(()),
+        Err(err) => err,
+    };
+    let to_end = |i: usize| i..pattern.len();
+    let span = match err.kind() {
+        ErrorKind::UnclosedClass => pattern.rfind('[').map(to_end),
+        ErrorKind::UnclosedAlternates => pattern.rfind('{').map(to_end),
+        ErrorKind::UnopenedAlternates => unbalanced_brace(pattern, '}').map(|i| i..i + 1),
+        ErrorKind::NestedAlternates => unbalanced_brace(pattern, '{').map(|i| i..i + 1),
+        ErrorKind::DanglingEscape => pattern.rfind('\\').map(to_end),
+        ErrorKind::InvalidRange(start, end) => {
+            let range = format!("{}-{}", start, end);
+            pattern.find(&range).map(|i| i..i + range.len())
+        }
+        ErrorKind::InvalidRecursive => pattern.find("**").map(|i| i..i + 2),
+        _ => None,
+    };
+    Err(PatternError::new(
+        err.kind().to_string(),
+        span.unwrap_or(0..pattern.len()),
+    ))
+}
+
+/// Where the first `}` closing nothing is, or the first `{` opened inside
+/// another, depending on `brace`.
+fn unbalanced_brace(pattern: &str, brace: char) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, ch) in pattern.char_indices() {
+        match ch {
+            '{' if brace == '{' && depth > 0 => return Some(i),
+            '{' => depth += 1,
+            '}' if brace == '}' && depth == 0 => return Some(i),
+            '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The error of a regex that doesn't parse. The position comes from the
+/// carets under the pattern in the message, or covers it all without them.
+pub fn regex_error(pattern: &str, err: &regex::Error) -> PatternError {
+    let text = err.to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    let message = lines
+        .iter()
+        .rev()
+        .find_map(|line| line.strip_prefix("error: "))
+        .unwrap_or(&text);
+    // the pattern and the carets below it are both indented by 4 columns
+    let span = lines
+        .iter()
+        .find(|line| line.trim_start().starts_with('^'))
+        .and_then(|carets| {
+            let column = carets.find('^')?.checked_sub(4)?;
+            let width = carets.trim().len();
+            let start = pattern.char_indices().nth(column)?.0;
+            let end = pattern
+                .char_indices()
+                .nth(column + width)
+                .map_or(pattern.len(), |(i, _)| i);
+            Some(start..end)
+        });
+    PatternError::new(message, span.unwrap_or(0..pattern.len()))
+}
+
+pub enum SearchMessage {
+    Found(PathEntry),
+    Done,
+}
+
+/// A search running on a worker thread.
+pub struct Search {
+    receiver: Receiver<SearchMessage>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Search {
+    /// Sends every path matching `globs` and meeting every one of `criteria`,
+    /// see `Globs::for_each_path`.
+    pub fn glob(
+        globs: Globs,
+        criteria: Vec<Criterion>,
+        respect_ignore: bool,
+        threads: usize,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancelled);
+
+        thread::spawn(move || {
+            globs.for_each_path(respect_ignore, threads, |path| {
+                if flag.load(Ordering::Relaxed) {
+                    return false;
+                }
+                let entry = PathEntry::new(path);
+                !meets(&entry, &criteria) || sender.send(SearchMessage::Found(entry)).is_ok()
+            });
+            if !flag.load(Ordering::Relaxed) {
+                let _ = sender.send(SearchMessage::Done);
+            }
+        });
+
+        Search {
+            receiver,
+            cancelled,
+        }
+    }
+
+    /// Walks `root` for text files containing `query`.
+    pub fn content(
+        root: PathBuf,
+        query: String,
+        case_sensitive: bool,
+        criteria: Vec<Criterion>,
+        respect_ignore: bool,
+        threads: usize,
+    ) -> Self {
+        let query = if case_sensitive {
+            query
+        } else {
+            query.to_lowercase()
+        };
+
+        Search::walk(
+            root,
+            criteria,
+            respect_ignore,
+            threads,
+            move |path, is_file| {
+                // only regular files, a FIFO would block the read forever
+                is_file && file_contains(path, &query, case_sensitive)
+            },
+        )
+    }
+
+    /// Walks `root` for entries whose full path matches `regex`.
+    pub fn regex(
+        root: PathBuf,
+        regex: Regex,
+        criteria: Vec<Criterion>,
+        respect_ignore: bool,
+        threads: usize,
+    ) -> Self {
+        Search::walk(root, criteria, respect_ignore, threads, move |path, _| {
+            regex.is_match(&path.to_string_lossy())
+        })
+    }
+
+    /// Sends every entry below `root` accepted by `matches` and meeting every
+    /// one of `criteria` from a worker thread. `matches` gets the path and
+    /// whether it's a regular file.
+    fn walk<F>(
+        root: PathBuf,
+        criteria: Vec<Criterion>,
+        respect_ignore: bool,
+        threads: usize,
+        matches: F,
+    ) -> Self
+    where
+        F: Fn(&Path, bool) -> bool + Send + Sync + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancelled);
+
+        thread::spawn(move || {
+            walk_each(&root, respect_ignore, None, threads, |path, is_file| {
+                if flag.load(Ordering::Relaxed) {
+                    return false;
+                }
+                if path == root || !matches(&path, is_file) {
+                    return true;
+                }
+                let entry = PathEntry::new(path);
+                !meets(&entry, &criteria) || sender.send(SearchMessage::Found(entry)).is_ok()
+            });
+            if !flag.load(Ordering::Relaxed) {
+                let _ = sender.send(SearchMessage::Done);
+            }
+        });
+
+        Search {
+            receiver,
+            cancelled,
+        }
+    }
+
+    /// Asks the worker to stop at the next entry.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Every remaining message, blocking until the worker is done.
+    pub fn wait(&self) -> Vec<SearchMessage> {
+        let mut messages = vec![];
+        for message in self.receiver.iter() {
+            let done = matches!(message, SearchMessage::Done);
+            messages.push(message);
+            if done {
+                return messages;
+            }
+        }
+        messages.push(SearchMessage::Done);
+        messages
+    }
+
+    /// Messages received so far, without blocking. A worker that went away
+    /// counts as done.
+    pub fn poll(&self) -> Vec<SearchMessage> {
+        let mut messages = vec![];
+        loop {
+            match self.receiver.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    messages.push(SearchMessage::Done);
+                    break;
+                }
+            }
+        }
+        messages
+    }
+}
+
+/// Whether `entry` meets every one of `criteria`. Sizing a directory walks
+/// it, which is why this runs on the workers.
+fn meets(entry: &PathEntry, criteria: &[Criterion]) -> bool {
+    criteria.iter().all(|criterion| criterion.matches(entry))
+}
+
+/// Paths below `root` down to `max_depth`, `root` first, with whether each
+/// is a regular file. Siblings come sorted by name. With `respect_ignore`,
+/// whatever `.gitignore` or `IGNORE_FILE` lists is skipped, ignored
+/// directories aren't entered at all.
+fn walk_paths(
+    root: &Path,
+    respect_ignore: bool,
+    max_depth: Option<usize>,
+) -> Box<dyn Iterator<Item = (PathBuf, bool)>> {
+    if !respect_ignore {
+        let entries = WalkDir::new(root)
+            .max_depth(max_depth.unwrap_or(usize::MAX))
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(Result::ok);
+        return Box::new(entries.map(|entry| {
+            let is_file = entry.file_type().is_file();
+            (entry.into_path(), is_file)
+        }));
+    }
+
+    let entries = walk_builder(root, true, max_depth)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+        .filter_map(Result::ok);
+    Box::new(entries.map(|entry| {
+        let is_file = entry.file_type().is_some_and(|kind| kind.is_file());
+        (entry.into_path(), is_file)
+    }))
+}
+
+/// Calls `visit` with each path `walk_paths` would give and whether it's a
+/// regular file, from `threads` threads, 0 for one per CPU. With 1 thread
+/// the paths come in order, on this thread. The walk stops once `visit`
+/// returns false, then false is returned.
+fn walk_each<F>(
+    root: &Path,
+    respect_ignore: bool,
+    max_depth: Option<usize>,
+    threads: usize,
+    visit: F,
+) -> bool
+where
+    F: Fn(PathBuf, bool) -> bool + Sync,
+{
+    if threads == 1 {
+        return walk_paths(root, respect_ignore, max_depth)
+            .all(|(path, is_file)| visit(path, is_file));
+    }
+
+    let stopped = AtomicBool::new(false);
+    walk_builder(root, respect_ignore, max_depth)
+        .threads(threads)
+        .build_parallel()
+        .run(|| {
+            let (visit, stopped) = (&visit, &stopped);
+            Box::new(move |entry| {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(_) => return WalkState::Continue,
+                };
+                let is_file = entry.file_type().is_some_and(|kind| kind.is_file());
+                if visit(entry.into_path(), is_file) {
+                    WalkState::Continue
+                } else {
+                    stopped.store(true, Ordering::Relaxed);
+                    WalkState::Quit
+                }
+            })
+        });
+    !stopped.load(Ordering::Relaxed)
+}
+
+/// A walker like `walk_paths` uses, without sorting. Without
+/// `respect_ignore` it walks everything like `WalkDir` does.
+fn walk_builder(root: &Path, respect_ignore: bool, max_depth: Option<usize>) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder.max_depth(max_depth);
+    match respect_ignore {
+        true => builder
+            .hidden(false)
+            .require_git(false)
+            .add_custom_ignore_filename(IGNORE_FILE),
+        false => builder.standard_filters(false),
+    };
+    builder
+}
+
+/// The leading directories of a glob pattern that hold no wildcards.
+fn literal_prefix(pattern: &str) -> PathBuf {
+    Path::new(pattern)
+        .components()
+        .take_while(|component| {
+            !component
+                .as_os_str()
+                .to_string_lossy()
+                .contains(['*', '?', '[', '{'])
+        })
+        .collect()
+}
+
+/// Whether the text file at `path` contains `query`, which must already be
+/// lowercase when matching case insensitively. Binary files never match.
+pub fn file_contains(path: &Path, query: &str, case_sensitive: bool) -> bool {
+    let mut bytes = vec![];
+    let read =
+        File::open(path).and_then(|file| file.take(CONTENT_READ_LIMIT).read_to_end(&mut bytes));
+    if read.is_err() {
+        return false;
+    }
+
+    let sniff_len = bytes.len().min(BINARY_SNIFF_LEN);
+    if bytes[..sniff_len].contains(&0) {
+        return false;
+    }
+
+    let text = String::from_utf8_lossy(&bytes);
+    if case_sensitive {
+        text.contains(query)
+    } else {
+        text.to_lowercase().contains(query)
+    }
+}
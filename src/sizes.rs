@@ -0,0 +1,46 @@
+use crate::dir_stats;
+use std::{
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+/// Sums the content of directories on a worker thread, one at a time.
+pub struct SizeScanner {
+    requests: Sender<PathBuf>,
+    results: Receiver<(PathBuf, u64)>,
+}
+
+impl SizeScanner {
+    pub fn new() -> Self {
+        let (requests, request_receiver) = mpsc::channel::<PathBuf>();
+        let (result_sender, results) = mpsc::channel();
+
+        // ends once the scanner, and with it the request sender, is dropped
+        thread::spawn(move || {
+            for path in request_receiver {
+                let (_, size) = dir_stats(&path);
+                if result_sender.send((path, size)).is_err() {
+                    return;
+                }
+            }
+        });
+
+        SizeScanner { requests, results }
+    }
+
+    pub fn request(&self, path: PathBuf) {
+        let _ = self.requests.send(path);
+    }
+
+    /// Sizes computed so far, without blocking.
+    pub fn poll(&self) -> Vec<(PathBuf, u64)> {
+        self.results.try_iter().collect()
+    }
+}
+
+impl Default for SizeScanner {
+    fn default() -> Self {
+        SizeScanner::new()
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeName {
+    Dark,
+    Light,
+    HighContrast,
+    /// Terminal default colors only, picked when `NO_COLOR` is set.
+    NoColor,
+}
+
+impl ThemeName {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThemeName::Dark => "dark",
+            ThemeName::Light => "light",
+            ThemeName::HighContrast => "high contrast",
+            ThemeName::NoColor => "no color",
+        }
+    }
+
+    pub fn next(&self) -> ThemeName {
+        match self {
+            ThemeName::Dark => ThemeName::Light,
+            ThemeName::Light => ThemeName::HighContrast,
+            ThemeName::HighContrast => ThemeName::NoColor,
+            ThemeName::NoColor => ThemeName::Dark,
+        }
+    }
+}
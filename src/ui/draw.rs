@@ -0,0 +1,1455 @@
+use super::theme::Theme;
+use crate::{
+    config::{Column, Density},
+    filesystem_id,
+    filter::Criterion,
+    humanize,
+    search::MatchEngine,
+    App, AppMode, Area, ConfirmAction, DirDiff, MarkView, PathEntry,
+};
+use std::{ops::Range, time::Duration};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Corner, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
+    symbols::bar,
+    text::{Span, Spans, Text},
+    widgets::{
+        BarChart, Block, BorderType, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph,
+        Wrap,
+    },
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+const SPINNER: [&str; 4] = ["|", "/", "-", "\\"];
+
+/// Where the path goes among the spans of a compact row.
+const COMPACT_PATH: usize = 6;
+/// Histogram bars drawn with `ascii_mode`.
+const ASCII_BARS: bar::Set = bar::Set {
+    full: "#",
+    seven_eighths: "#",
+    three_quarters: "#",
+    five_eighths: "#",
+    half: "=",
+    three_eighths: "=",
+    one_quarter: "-",
+    one_eighth: "-",
+    empty: " ",
+};
+
+/// Draws the whole interface for `app`, and records the layout it needs
+/// to handle clicks.
+pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let bg_box = Block::default().style(Style::default().bg(theme(app).background));
+    f.render_widget(bg_box, f.size());
+
+    let screen_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(f.size());
+
+    // build footer with the mode badge
+
+    let colors = &app.config.mode_colors;
+    let badge_color = match app.app_mode {
+        AppMode::Normal => &colors.normal,
+        AppMode::Insert => &colors.insert,
+        AppMode::Prompt => &colors.prompt,
+        AppMode::Presets => &colors.presets,
+        AppMode::Confirm => &colors.confirm,
+        AppMode::Quarantine => &colors.quarantine,
+        AppMode::Command => &colors.command,
+        AppMode::Visual => &colors.visual,
+    };
+    let mut footer = vec![Span::styled(
+        format!(" -- {} -- ", app.app_mode.name()),
+        theme(app).badge(parse_color(badge_color).unwrap_or(theme(app).muted)),
+    )];
+    if app.dry_run {
+        footer.push(Span::styled(
+            " DRY RUN ",
+            theme(app).badge(theme(app).warning),
+        ));
+    }
+    if let Some(count) = app.count {
+        footer.push(Span::raw(format!(" {}", count)));
+    }
+    // the command line goes after the badge, like in vi
+    if let AppMode::Command = app.app_mode {
+        let start = Spans::from(footer.clone()).width() as u16 + 1;
+        app.command_line
+            .scroll_to_cursor(screen_chunks[1].width.saturating_sub(start + 1) as usize);
+        let (content, cursor) = app.command_line.visible();
+        footer.push(Span::styled(
+            format!(" :{}", content),
+            Style::default().fg(theme(app).input),
+        ));
+        f.set_cursor(
+            screen_chunks[1].x + start + cursor as u16 + 1,
+            screen_chunks[1].y,
+        );
+    }
+    f.render_widget(Paragraph::new(Spans::from(footer)), screen_chunks[1]);
+
+    let main_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage(app.config.split_ratio),
+                Constraint::Percentage(100 - app.config.split_ratio),
+            ]
+            .as_ref(),
+        )
+        .split(screen_chunks[0]);
+
+    let left_area = main_chunks[0];
+    let right_area = main_chunks[1];
+
+    // build left side
+    let left_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints(
+            [
+                Constraint::Length(app.config.show_help as u16),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(3),
+                Constraint::Length(app.pattern_error.is_some() as u16),
+                Constraint::Length(if app.config.show_histogram { 10 } else { 0 }),
+                Constraint::Min(0),
+                Constraint::Length(if app.command_log.is_empty() { 0 } else { 12 }),
+            ]
+            .as_ref(),
+        )
+        .split(left_area);
+
+    // build help message
+
+    let (msg, style) = match app.app_mode {
+        AppMode::Normal => (
+            vec![
+                Span::styled("[i]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("nsert mode"),
+                Span::raw(", "),
+                Span::styled("[~]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" toogle case sensitive matches, "),
+                Span::styled("[e]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("ngine, "),
+                Span::styled("[w]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("atch, "),
+                Span::styled("[f]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" skip ignored files, "),
+                Span::styled("[r]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("efresh, "),
+                Span::styled("[R]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("eset options, "),
+                Span::styled("[c]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("hange root, "),
+                Span::styled("[t]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("ime filter, "),
+                Span::styled("[H]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("istogram, "),
+                Span::styled("[T]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("heme, "),
+                Span::styled("[s]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("ort, "),
+                Span::styled("[-]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" reverse sort, "),
+                Span::styled("[!]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" shell command, "),
+                Span::styled("[p]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("resets, "),
+                Span::styled("[Q]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("uarantine, "),
+                Span::styled("[x]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" run a command on the marked, "),
+                Span::styled("[:]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" command line, "),
+                Span::styled("[V]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("isual marking, "),
+                Span::styled("[m]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" show marked/unmarked/all, "),
+                Span::styled("[B]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("ack up then delete, "),
+                Span::styled("[P]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" save preset, "),
+                Span::styled("[C]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("ompare patterns, "),
+                Span::styled("[z]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" density, "),
+                Span::styled("[< >]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" resize, "),
+                Span::styled("[?]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" hide help, "),
+                Span::styled("[q]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("uit"),
+            ],
+            Style::default(),
+        ),
+        AppMode::Insert => (
+            vec![
+                Span::styled("[Enter]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" set the pattern, "),
+                Span::styled("[Up Down]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" history, "),
+                Span::styled("[Esc]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" exit insert mode, separate patterns with spaces or commas, "),
+                Span::styled("!glob", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" excludes, "),
+                Span::styled(
+                    "+mtime:30d -mtime:7d",
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" older/newer than, "),
+                Span::styled(
+                    "size>100M size<1K",
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" larger/smaller than"),
+            ],
+            Style::default(),
+        ),
+        AppMode::Confirm => (
+            vec![Span::styled(
+                "Waiting for confirmation",
+                Style::default().add_modifier(Modifier::ITALIC),
+            )],
+            Style::default(),
+        ),
+        AppMode::Presets => (
+            vec![
+                Span::styled("[Enter]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" search with the preset, "),
+                Span::styled("[Esc]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" close"),
+            ],
+            Style::default(),
+        ),
+        AppMode::Quarantine => (
+            vec![
+                Span::styled("[Enter r]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" restore the batch, "),
+                Span::styled("[x]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" purge it, "),
+                Span::styled("[Esc]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" close"),
+            ],
+            Style::default(),
+        ),
+        AppMode::Visual => (
+            vec![
+                Span::styled("[j k g G]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" mark up to, "),
+                Span::styled("[V Enter]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" keep the marks, "),
+                Span::styled("[d]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" delete the marked, "),
+                Span::styled("[Esc]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" put the marks back"),
+            ],
+            Style::default(),
+        ),
+        AppMode::Command => (
+            vec![
+                Span::styled("[Enter]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" run, "),
+                Span::styled("[Esc]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" cancel, "),
+                Span::styled(
+                    "sort[!] name|size|mtime|type|none",
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(", "),
+                Span::styled(
+                    "filter query",
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(", "),
+                Span::styled("delete[!]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(", "),
+                Span::styled("cd dir", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(", "),
+                Span::styled("w file", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(", "),
+                Span::styled("q[!]", Style::default().add_modifier(Modifier::BOLD)),
+            ],
+            Style::default(),
+        ),
+        AppMode::Prompt => (
+            vec![
+                Span::styled("[Enter]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" submit, "),
+                Span::styled("[Esc]", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" cancel"),
+            ],
+            Style::default(),
+        ),
+    };
+
+    let mut text = Text::from(Spans::from(msg));
+    text.patch_style(style);
+    let help_message = Paragraph::new(text);
+
+    f.render_widget(help_message, left_chunks[0]);
+
+    // display current pattern
+
+    let case_text = if app.is_case_sensitive() { "ON" } else { "OFF" };
+    let sort_text = match app.sort {
+        Some(key) if app.sort_descending => format!("{} desc", key.name()),
+        Some(key) => format!("{} asc", key.name()),
+        None => String::from("none"),
+    };
+    let live_text = if app.config.keep_sorted {
+        " (live)"
+    } else {
+        ""
+    };
+    let scope_text = match app.engine {
+        MatchEngine::Glob if app.is_absolute_pattern() => String::from(" (absolute)"),
+        _ => format!(" (in {})", app.root.display()),
+    };
+    let spinner = match app.is_searching() {
+        true => format!(
+            "{} {} found",
+            SPINNER[app.spinner % SPINNER.len()],
+            app.list.items.len()
+        ),
+        false => String::new(),
+    };
+
+    let spans = match app.pattern.content.is_empty() {
+        false => Spans::from(vec![
+            Span::raw("Searching: "),
+            Span::styled(
+                app.pattern.content.as_str(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(scope_text),
+            Span::raw(", case sensitive: "),
+            Span::styled(case_text, Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(", engine: "),
+            Span::styled(
+                app.engine.name(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(", sort: "),
+            Span::styled(sort_text, Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(live_text),
+            Span::raw(if app.watcher.is_some() {
+                ", watching"
+            } else {
+                ""
+            }),
+            Span::raw(if app.respect_ignore() {
+                ", skipping ignored"
+            } else {
+                ""
+            }),
+            Span::raw(match app.criteria.is_empty() {
+                true => String::new(),
+                false => format!(
+                    ", filters: {}",
+                    app.criteria
+                        .iter()
+                        .map(Criterion::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }),
+            Span::raw(" "),
+            Span::styled(spinner, Style::default().add_modifier(Modifier::BOLD)),
+        ]),
+        true => Spans::from(vec![
+            Span::styled(
+                "Empty pattern, try inserting a new one",
+                Style::default().add_modifier(Modifier::ITALIC),
+            ),
+            Span::raw(format!(" (in {})", app.root.display())),
+        ]),
+    };
+
+    let mut text = Text::from(spans);
+    text.patch_style(Style::default().fg(theme(app).accent));
+    f.render_widget(Paragraph::new(text), left_chunks[1]);
+
+    // display status message
+
+    if let Some(status) = &app.status {
+        let text = Span::styled(status.as_str(), Style::default().fg(theme(app).error));
+        f.render_widget(Paragraph::new(text), left_chunks[2]);
+    }
+
+    // display input
+
+    let active_area = left_chunks[3];
+    app.pattern
+        .scroll_to_cursor(active_area.width.saturating_sub(2) as usize);
+    let (content, cursor) = app.pattern.visible();
+    let style = match app.app_mode {
+        AppMode::Insert => Style::default().fg(theme(app).input),
+        AppMode::Normal
+        | AppMode::Prompt
+        | AppMode::Presets
+        | AppMode::Confirm
+        | AppMode::Quarantine
+        | AppMode::Command
+        | AppMode::Visual => Style::default(),
+    };
+
+    // the part of the pattern at fault, in view
+    let hidden = app.pattern.content.len() - content.len();
+    let fault = app.pattern_error.as_ref().map(|err| {
+        let start = err.span.start.saturating_sub(hidden).min(content.len());
+        start
+            ..err
+                .span
+                .end
+                .saturating_sub(hidden)
+                .clamp(start, content.len())
+    });
+    let text = match &fault {
+        Some(fault) => Spans::from(vec![
+            Span::raw(&content[..fault.start]),
+            Span::styled(
+                &content[fault.clone()],
+                Style::default()
+                    .fg(theme(app).error)
+                    .add_modifier(Modifier::UNDERLINED | Modifier::BOLD),
+            ),
+            Span::raw(&content[fault.end..]),
+        ]),
+        None => Spans::from(content),
+    };
+    let pattern_input = create_input(&app.pattern.name, text, style, border_type(app));
+    f.render_widget(pattern_input, active_area);
+
+    if let (Some(err), Some(fault)) = (&app.pattern_error, fault) {
+        let indent = content[..fault.start].width() + 1;
+        let carets = "^".repeat(content[fault].width().max(1));
+        let line = format!("{}{} {}", " ".repeat(indent), carets, err.message);
+        let text = Span::styled(line, Style::default().fg(theme(app).error));
+        f.render_widget(Paragraph::new(text), left_chunks[4]);
+    }
+
+    match app.app_mode {
+        AppMode::Normal
+        | AppMode::Prompt
+        | AppMode::Presets
+        | AppMode::Confirm
+        | AppMode::Quarantine
+        | AppMode::Command
+        | AppMode::Visual => {}
+        AppMode::Insert => f.set_cursor(active_area.x + cursor as u16 + 1, active_area.y + 1),
+    }
+
+    // display size histogram
+
+    if app.config.show_histogram {
+        let histogram = app.size_histogram();
+        let data: Vec<(&str, u64)> = histogram
+            .iter()
+            .map(|(label, count)| (label.as_str(), *count))
+            .collect();
+        let chart = BarChart::default()
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(border_type(app))
+                    .title("Sizes"),
+            )
+            .data(&data)
+            .bar_width(5)
+            .bar_set(if app.config.ascii_mode {
+                ASCII_BARS
+            } else {
+                bar::NINE_LEVELS
+            })
+            .bar_style(Style::default().fg(theme(app).chart))
+            .value_style(theme(app).badge(theme(app).chart));
+        f.render_widget(chart, left_chunks[5]);
+    }
+
+    // display preview of the selected entry
+
+    if app.config.show_preview {
+        let area = left_chunks[6];
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(border_type(app))
+            .title("Preview");
+        let lines: Vec<Spans> = app
+            .selected_preview(area.height.saturating_sub(2) as usize)
+            .unwrap_or_default()
+            .iter()
+            .map(|line| Spans::from(line.as_str()))
+            .collect();
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    // display the output of the external command
+
+    if !app.command_log.is_empty() {
+        let area = left_chunks[7];
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(border_type(app))
+            .title("Command output, [Esc] to close");
+        let rows = area.height.saturating_sub(2) as usize;
+        let lines: Vec<Spans> = app
+            .command_log
+            .iter()
+            .skip(app.command_log.len().saturating_sub(rows))
+            .map(|line| Spans::from(line.as_str()))
+            .collect();
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    // end build left side
+
+    // build right side
+    let right_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints(match app.config.show_help {
+            true => [Constraint::Percentage(5), Constraint::Percentage(95)],
+            false => [Constraint::Length(0), Constraint::Min(0)],
+        })
+        .split(right_area);
+
+    let spans = Spans::from(vec![
+        Span::styled("[Enter]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" toggle entry deletion, "),
+        Span::styled("[a A v]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" mark all/none/invert, "),
+        Span::styled("[d]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw("elete active entries, "),
+        Span::styled("[dd]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" delete the selected, "),
+        Span::styled("[X]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" delete skipping the trash, "),
+        Span::styled("[u Ctrl+R]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" undo/redo marks or delete, "),
+        Span::styled("[n]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" dry run mode, "),
+        Span::styled("[ ]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" previous/next directory, "),
+        Span::styled("[gg G]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" first/last, "),
+        Span::styled(
+            "[double click]",
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(" toggle, "),
+        Span::styled("[/]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" filter, "),
+        Span::styled("[l]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" expand directory, "),
+        Span::styled("[b]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" tree view, "),
+        Span::styled("[F]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw("ind duplicates, "),
+        Span::styled("[h]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" collapse, "),
+        Span::styled("[I]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw("nspect, "),
+        Span::styled("[o O]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" open in the editor/with the system, "),
+        Span::styled("[y Y]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" copy the path/marked paths, "),
+        Span::styled("[E W]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" export the results/marked, "),
+        Span::styled("[Tab]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" preview, "),
+        Span::styled("[D]", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" dry run"),
+    ]);
+
+    let help_style = match app.app_mode {
+        AppMode::Normal => Style::default(),
+        AppMode::Insert
+        | AppMode::Prompt
+        | AppMode::Presets
+        | AppMode::Confirm
+        | AppMode::Quarantine
+        | AppMode::Command
+        | AppMode::Visual => Style::default().fg(theme(app).muted),
+    };
+
+    let mut text = Text::from(spans);
+    text.patch_style(help_style);
+    let help_text = Paragraph::new(text);
+    f.render_widget(help_text, right_chunks[0]);
+
+    let chunk_width = right_area.width as usize;
+    let density = app.density();
+    let rows_per_entry = match density {
+        Density::Compact => 1,
+        Density::Comfortable | Density::Dense => {
+            1 + app.config.columns.contains(&Column::Path) as u16
+                + (density == Density::Comfortable) as u16
+        }
+    };
+    app.page_size = (right_chunks[1].height.saturating_sub(2) / rows_per_entry).max(1) as usize;
+    app.rows_per_entry = rows_per_entry;
+    let inner = right_chunks[1].inner(&Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    app.list_area = Area::new(inner.x, inner.y, inner.width, inner.height);
+    let offset = app.update_list_offset();
+    let root_filesystem = filesystem_id(&app.root);
+    let covered = app.covered_paths();
+    let visual = app.visual_range().unwrap_or_default();
+
+    let mut items: Vec<ListItem> = app
+        .list
+        .items
+        .iter()
+        .enumerate()
+        // only the rows in view, the selection is kept in view by the offset
+        .skip(offset)
+        .take(app.page_size)
+        .map(|(i, entry)| {
+            if entry.group {
+                return group_item(app, i, entry, rows_per_entry);
+            }
+            let (turbo, turbo_color) = match entry.is_delete() {
+                true => ("o <> o", theme(app).danger),
+                false => ("- <> -", theme(app).muted),
+            };
+
+            let mut header: Vec<Span> = match density {
+                Density::Compact => vec![
+                    Span::styled(&turbo[..1], Style::default().fg(turbo_color)),
+                    Span::raw(" "),
+                    Span::styled(
+                        type_glyph(entry).to_string(),
+                        Style::default().fg(theme(app).file_type),
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:>6}", app.column_text(entry, Column::Size)),
+                        column_span(app, entry, Column::Size).style,
+                    ),
+                    Span::raw(" "),
+                    // fit to the room the badges leave below
+                    Span::raw(""),
+                ],
+                Density::Comfortable | Density::Dense => {
+                    let mut header: Vec<Span> = app
+                        .config
+                        .columns
+                        .iter()
+                        .filter(|column| **column != Column::Path)
+                        .flat_map(|column| [column_span(app, entry, *column), Span::raw(" ")])
+                        .collect();
+                    header.push(Span::styled(turbo, Style::default().fg(turbo_color)));
+                    header
+                }
+            };
+            if covered.contains(entry.pathbuf.as_path()) {
+                header.push(Span::styled(
+                    " [covered by parent]",
+                    Style::default().fg(theme(app).muted),
+                ));
+            }
+            if entry.filesystem.is_some() && entry.filesystem != root_filesystem {
+                header.push(Span::styled(
+                    " [other filesystem]",
+                    Style::default().fg(theme(app).warning),
+                ));
+            }
+            if let Some(err) = &entry.error {
+                header.push(Span::styled(
+                    format!(" [{}]", err),
+                    Style::default().fg(theme(app).error),
+                ));
+            }
+            if let Some(group) = app.duplicate_groups.get(&entry.pathbuf) {
+                header.push(Span::styled(
+                    format!(" [dup group {}]", group),
+                    Style::default().fg(theme(app).accent),
+                ));
+            }
+            if let Some(hidden) = app.collapsed_count(&entry.pathbuf) {
+                header.push(Span::styled(
+                    format!(" [{} hidden]", hidden),
+                    Style::default().fg(theme(app).muted),
+                ));
+            }
+
+            let path_width = inner.width as usize;
+            if density == Density::Compact {
+                let taken: usize = header.iter().map(Span::width).sum();
+                let path = path_spans(app, entry, path_width.saturating_sub(taken));
+                header.splice(COMPACT_PATH..=COMPACT_PATH, path);
+            }
+            let mut lines = vec![Spans::from(header)];
+            if density != Density::Compact && app.config.columns.contains(&Column::Path) {
+                lines.push(Spans::from(path_spans(app, entry, path_width)));
+            }
+            if density == Density::Comfortable {
+                lines.push(Spans::from("-".repeat(chunk_width)));
+            }
+
+            // the visual range looks selected as a whole
+            let style = match visual.contains(&i) {
+                true => theme(app).selection(),
+                false => Style::default()
+                    .fg(theme(app).text)
+                    .bg(theme(app).background),
+            };
+            ListItem::new(lines).style(style)
+        })
+        .collect();
+
+    // tell a search that found nothing yet apart from one without matches
+    if items.is_empty() && app.searching {
+        let placeholder = if app.config.ascii_mode {
+            "Searching..."
+        } else {
+            "Searching…"
+        };
+        items.push(ListItem::new(Span::styled(
+            placeholder,
+            Style::default()
+                .fg(theme(app).muted)
+                .add_modifier(Modifier::ITALIC),
+        )));
+    }
+
+    let n = app.entries_to_delete().len();
+    let (marked_size, complete) = app.marked_size();
+    let spans = Spans::from(vec![
+        Span::raw("Entries to eradicate: "),
+        Span::styled(
+            n.to_string(),
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(theme(app).danger),
+        ),
+        Span::raw(", "),
+        Span::styled(
+            // still adding up directories
+            format!(
+                "{}{}",
+                humanize::size(marked_size),
+                if complete { "" } else { "+" }
+            ),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(match app.list.get_index() {
+            Some(i) => format!(", item {}/{}", i + 1, app.list.items.len()),
+            None => String::new(),
+        }),
+        Span::raw(match app.overflow.is_empty() {
+            true => String::new(),
+            false => {
+                let listed = app.listed_count();
+                format!(
+                    ", showing {} of {} matches, [L] for more",
+                    humanize::count(listed),
+                    humanize::count(listed + app.overflow.len())
+                )
+            }
+        }),
+        Span::raw(match app.filter.is_empty() {
+            true => String::new(),
+            false => format!(", filter: {}", app.filter),
+        }),
+        Span::raw(match app.mark_view {
+            MarkView::All => String::from(" "),
+            view => format!(", {} only ", view.name()),
+        }),
+    ]);
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(spans.0)
+                .border_type(border_type(app)),
+        )
+        .highlight_style(theme(app).selection().add_modifier(Modifier::ITALIC))
+        .start_corner(Corner::TopLeft);
+
+    let mut state = ListState::default();
+    state.select(app.list.get_index().map(|i| i - offset));
+    f.render_stateful_widget(list, right_chunks[1], &mut state);
+    render_scrollbar(f, app, right_chunks[1], offset);
+
+    // display prompt popup
+
+    let border = border_type(app);
+    let input_color = theme(app).input;
+    if let Some(prompt) = app.prompt.as_mut() {
+        let area = centered_rect(60, 3, f.size());
+        prompt
+            .input
+            .scroll_to_cursor(area.width.saturating_sub(2) as usize);
+        let (content, cursor) = prompt.input.visible();
+        let input = create_input(
+            &prompt.input.name,
+            content,
+            Style::default().fg(input_color),
+            border,
+        );
+        f.render_widget(Clear, area);
+        f.render_widget(input, area);
+        f.set_cursor(area.x + cursor as u16 + 1, area.y + 1);
+    }
+
+    // display preset picker
+
+    if let AppMode::Presets = app.app_mode {
+        let items: Vec<ListItem> = app
+            .presets
+            .items
+            .iter()
+            .map(|preset| {
+                ListItem::new(Spans::from(vec![
+                    Span::styled(
+                        preset.name.as_str(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("  "),
+                    Span::styled(
+                        preset.search_pattern(),
+                        Style::default().fg(theme(app).muted),
+                    ),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(border_type(app))
+                    .title("Quick clean"),
+            )
+            .highlight_style(theme(app).selection());
+
+        let height = app.presets.items.len() as u16 + 2;
+        let area = centered_rect(70, height, f.size());
+        f.render_widget(Clear, area);
+        let mut state = ListState::default();
+        state.select(app.presets.get_index());
+        f.render_stateful_widget(list, area, &mut state);
+    }
+
+    // display quarantined batches
+
+    if let AppMode::Quarantine = app.app_mode {
+        let items: Vec<ListItem> = app
+            .quarantine
+            .items
+            .iter()
+            .map(|batch| {
+                let manifest = &batch.manifest;
+                ListItem::new(Spans::from(vec![
+                    Span::styled(
+                        manifest.created.as_str(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(format!(
+                        "  {} entries, {}  ",
+                        manifest.entries.len(),
+                        humanize::size(manifest.size())
+                    )),
+                    Span::styled(
+                        manifest.root.display().to_string(),
+                        Style::default().fg(theme(app).muted),
+                    ),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(border_type(app))
+                    .title("Quarantine"),
+            )
+            .highlight_style(theme(app).selection());
+
+        let height = app.quarantine.items.len() as u16 + 2;
+        let area = centered_rect(70, height, f.size());
+        f.render_widget(Clear, area);
+        let mut state = ListState::default();
+        state.select(app.quarantine.get_index());
+        f.render_stateful_widget(list, area, &mut state);
+    }
+
+    // display deletion progress
+
+    if let Some(batch) = app.batch.as_ref().filter(|batch| batch.is_running()) {
+        let area = centered_rect(60, 4, f.size());
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(border_type(app))
+            .title("Eradicating, [Esc] to cancel");
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Length(1)].as_ref())
+            .split(block.inner(area));
+
+        let current = batch
+            .current()
+            .map(|path| path.strip_prefix(&app.root).unwrap_or(path))
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        let eta = batch
+            .eta()
+            .map(|eta| {
+                let eta = Duration::from_secs(eta.as_secs());
+                format!(", {} left", humantime::format_duration(eta))
+            })
+            .unwrap_or_default();
+        let label = format!(
+            "{}/{}, {} freed{}",
+            batch.done(),
+            batch.total(),
+            humanize::size(batch.freed()),
+            eta
+        );
+        let ratio = batch.done() as f64 / batch.total().max(1) as f64;
+        let gauge = Gauge::default()
+            .gauge_style(
+                Style::default()
+                    .fg(theme(app).danger)
+                    .bg(theme(app).background),
+            )
+            .ratio(ratio.min(1.0))
+            .label(label);
+
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+        f.render_widget(Paragraph::new(current), rows[0]);
+        f.render_widget(gauge, rows[1]);
+    }
+
+    // display archiving progress
+
+    if let Some(archiver) = &app.archiver {
+        let area = centered_rect(60, 3, f.size());
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(border_type(app))
+            .title("Archiving");
+        let text = format!(
+            "{} {} entries to {}",
+            SPINNER[app.spinner % SPINNER.len()],
+            archiver.count,
+            archiver.path.display()
+        );
+        f.render_widget(Clear, area);
+        f.render_widget(Paragraph::new(text).block(block), area);
+    }
+
+    // display confirmation dialog
+
+    if let Some(action) = &app.confirm {
+        let (question, no_text) = match action {
+            ConfirmAction::Delete {
+                permanent,
+                count,
+                only,
+            } => {
+                let size = confirm_size(app, app.marked_size_of(only.as_ref()));
+                let question = match (permanent, app.quarantine()) {
+                    (true, _) => {
+                        format!("Permanently eradicate {} marked entries ({})?", count, size)
+                    }
+                    (false, true) => {
+                        format!(
+                            "Move {} marked entries ({}) to the quarantine?",
+                            count, size
+                        )
+                    }
+                    (false, false) => {
+                        format!("Move {} marked entries ({}) to the trash?", count, size)
+                    }
+                };
+                (question, " no")
+            }
+            ConfirmAction::DeleteDir {
+                path,
+                children,
+                size,
+            } => (
+                format!(
+                    "Eradicate {} ({} entries, {})?",
+                    path.strip_prefix(&app.root).unwrap_or(path).display(),
+                    children,
+                    humanize::size(*size)
+                ),
+                " skip",
+            ),
+            ConfirmAction::Quit => {
+                let marked = app.get_entries_by(|e| e.is_delete()).len();
+                let question = match marked {
+                    0 => String::from("Quit?"),
+                    _ => format!("Quit with {} entries still marked?", marked),
+                };
+                (question, " no")
+            }
+            ConfirmAction::OverwritePreset(preset) => {
+                (format!("Overwrite the preset '{}'?", preset.name), " no")
+            }
+            ConfirmAction::Archive { count } => (
+                format!(
+                    "Archive {} marked entries ({}), then eradicate them?",
+                    count,
+                    confirm_size(app, app.marked_size())
+                ),
+                " no",
+            ),
+            ConfirmAction::PurgeQuarantine(batch) => (
+                format!(
+                    "Purge the {} entries quarantined on {} for good?",
+                    batch.manifest.entries.len(),
+                    batch.manifest.created
+                ),
+                " no",
+            ),
+        };
+        let keymap = &app.config.keymap;
+        let mut text = vec![Spans::from(Span::styled(
+            question,
+            Style::default().add_modifier(Modifier::BOLD),
+        ))];
+
+        let cross_mount = match action {
+            ConfirmAction::Delete { .. } | ConfirmAction::Archive { .. } => app
+                .get_entries_by(|e| e.is_delete() && app.is_cross_mount(e))
+                .len(),
+            ConfirmAction::DeleteDir { .. }
+            | ConfirmAction::Quit
+            | ConfirmAction::OverwritePreset(_)
+            | ConfirmAction::PurgeQuarantine(_) => 0,
+        };
+        if cross_mount > 0 {
+            text.push(Spans::from(Span::styled(
+                format!(
+                    "{} of them are on another filesystem, this may be slow",
+                    cross_mount
+                ),
+                Style::default().fg(theme(app).warning),
+            )));
+        }
+
+        text.push(Spans::from(vec![
+            Span::styled(
+                format!("[{}]", keymap.confirm.join("/")),
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(theme(app).danger),
+            ),
+            Span::raw(" yes, "),
+            Span::styled(
+                format!("[{}]", keymap.cancel.join("/")),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(no_text),
+        ]));
+        let height = text.len() as u16 + 2;
+        let dialog = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(border_type(app))
+                .title("Confirm"),
+        );
+        let area = centered_rect(50, height, f.size());
+        f.render_widget(Clear, area);
+        f.render_widget(dialog, area);
+    }
+
+    // display pattern comparison
+
+    if let Some(diff) = &app.pattern_diff {
+        let area = centered_rect(90, f.size().height * 8 / 10, f.size());
+        f.render_widget(Clear, area);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Ratio(1, 3),
+                    Constraint::Ratio(1, 3),
+                    Constraint::Ratio(1, 3),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        let sections = [
+            (
+                format!("Only in {}", diff.pattern_a),
+                &diff.only_a,
+                theme(app).danger,
+            ),
+            (
+                format!("Only in {}", diff.pattern_b),
+                &diff.only_b,
+                theme(app).success,
+            ),
+            (String::from("In both"), &diff.both, theme(app).muted),
+        ];
+        for ((title, paths, color), area) in sections.into_iter().zip(columns) {
+            let items: Vec<ListItem> = paths
+                .iter()
+                .map(|path| {
+                    let path = path.strip_prefix(&app.root).unwrap_or(path);
+                    ListItem::new(path.display().to_string())
+                })
+                .collect();
+            let list = List::new(items).style(Style::default().fg(color)).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(border_type(app))
+                    .title(format!("{} ({})", title, paths.len())),
+            );
+            f.render_widget(list, area);
+        }
+    }
+
+    // display dry run before/after
+
+    if let Some(diffs) = &app.tree_diff {
+        let area = centered_rect(90, f.size().height * 8 / 10, f.size());
+        f.render_widget(Clear, area);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
+            .split(area);
+
+        let dir_header = |diff: &DirDiff| {
+            let dir = diff.dir.strip_prefix(&app.root).unwrap_or(&diff.dir);
+            ListItem::new(Span::styled(
+                format!("{}/", dir.display()),
+                Style::default().add_modifier(Modifier::BOLD),
+            ))
+        };
+        let mut before = vec![];
+        let mut after = vec![];
+        for diff in diffs {
+            before.push(dir_header(diff));
+            for name in &diff.before {
+                let color = match diff.after.contains(name) {
+                    true => theme(app).muted,
+                    false => theme(app).danger,
+                };
+                before.push(ListItem::new(Span::styled(
+                    format!("  {}", name),
+                    Style::default().fg(color),
+                )));
+            }
+            after.push(dir_header(diff));
+            for name in &diff.after {
+                after.push(ListItem::new(Span::styled(
+                    format!("  {}", name),
+                    Style::default().fg(theme(app).muted),
+                )));
+            }
+        }
+
+        for ((title, items), area) in [("Before", before), ("After", after)]
+            .into_iter()
+            .zip(columns)
+        {
+            let list = List::new(items).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(border_type(app))
+                    .title(title),
+            );
+            f.render_widget(list, area);
+        }
+    }
+
+    // display message popup
+
+    if let Some(popup) = &app.popup {
+        let width = f.size().width * 70 / 100;
+        let inner_width = width.saturating_sub(2).max(1) as usize;
+        let height: usize = popup
+            .lines
+            .iter()
+            .map(|line| (line.width() / inner_width) + 1)
+            .sum();
+        let area = centered_rect(70, height as u16 + 2, f.size());
+
+        let text: Vec<Spans> = popup
+            .lines
+            .iter()
+            .map(|line| Spans::from(line.as_str()))
+            .collect();
+        let paragraph = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(border_type(app))
+                .title(popup.title.as_str()),
+        );
+        f.render_widget(Clear, area);
+        f.render_widget(paragraph, area);
+    }
+}
+
+/// A rect of `percent_x` of the width and `height` rows, centered in `area`.
+fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(area.height.saturating_sub(height) / 2),
+                Constraint::Length(height),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical[1])[1]
+}
+
+/// Draws a scrollbar over the right border of the result list, when the
+/// entries don't all fit.
+fn render_scrollbar<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect, offset: usize) {
+    let total = app.list.items.len();
+    let height = area.height.saturating_sub(2) as usize;
+    if total <= app.page_size || height == 0 {
+        return;
+    }
+
+    let thumb_len = (height * app.page_size / total).clamp(1, height);
+    let thumb_start = (height * offset / total).min(height - thumb_len);
+    let (thumb, track) = match app.config.ascii_mode {
+        true => ("#", "|"),
+        false => ("█", "│"),
+    };
+    let lines: Vec<Spans> = (0..height)
+        .map(
+            |row| match row >= thumb_start && row < thumb_start + thumb_len {
+                true => Spans::from(Span::styled(thumb, Style::default().fg(theme(app).text))),
+                false => Spans::from(Span::styled(track, Style::default().fg(theme(app).muted))),
+            },
+        )
+        .collect();
+    let bar = Rect::new(area.right() - 1, area.y + 1, 1, height as u16);
+    f.render_widget(Paragraph::new(lines), bar);
+}
+
+/// Wheel moves the selection, a click selects an entry and a double click,
+/// or a click on its marker, toggles it.
+fn border_type(app: &App) -> BorderType {
+    if app.config.ascii_mode {
+        BorderType::Plain
+    } else {
+        BorderType::Rounded
+    }
+}
+
+/// The colors of the theme `app` is set to.
+fn theme(app: &App) -> Theme {
+    Theme::new(app.theme)
+}
+
+fn create_input<'a>(
+    name: &'a str,
+    text: impl Into<Text<'a>>,
+    style: Style,
+    border_type: BorderType,
+) -> Paragraph<'a> {
+    Paragraph::new(text).style(style).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .title(name),
+    )
+}
+
+fn column_span(app: &App, entry: &PathEntry, column: Column) -> Span<'static> {
+    let text = app.column_text(entry, column);
+    let color = match column {
+        Column::Type => theme(app).file_type,
+        Column::Size => size_color(app, entry).unwrap_or(theme(app).size),
+        Column::Mtime => theme(app).age,
+        Column::Permissions => theme(app).muted,
+        Column::Path => match size_color(app, entry) {
+            Some(color) => color,
+            None => return Span::raw(text),
+        },
+    };
+    Span::styled(text, Style::default().fg(color))
+}
+
+/// A size in a confirmation, until the directories in it are added up.
+fn confirm_size(app: &App, (size, complete): (u64, bool)) -> String {
+    match (complete, app.config.ascii_mode) {
+        (true, _) => humanize::size(size),
+        (false, false) => String::from("calculating…"),
+        (false, true) => String::from("calculating..."),
+    }
+}
+
+/// The path of the entry cut to `width` columns, see `humanize::truncate_path`,
+/// with what the pattern matched in it highlighted.
+fn path_spans(app: &App, entry: &PathEntry, width: usize) -> Vec<Span<'static>> {
+    let span = column_span(app, entry, Column::Path);
+    let text = span.content.to_string();
+    let ellipsis = if app.config.ascii_mode { "..." } else { "…" };
+    let (cut, filler) = humanize::path_cut(&text, width, ellipsis).unwrap_or_default();
+    let matched = app.match_spans(entry);
+    let highlight = span
+        .style
+        .fg(theme(app).accent)
+        .add_modifier(Modifier::BOLD);
+
+    let mut spans = highlighted(&text, 0..cut.start, &matched, span.style, highlight);
+    if filler {
+        spans.push(Span::styled(ellipsis, span.style));
+    }
+    spans.extend(highlighted(
+        &text,
+        cut.end..text.len(),
+        &matched,
+        span.style,
+        highlight,
+    ));
+    spans
+}
+
+/// `range` of `text` in spans, the parts within `matched` in `highlight`.
+fn highlighted(
+    text: &str,
+    range: Range<usize>,
+    matched: &[Range<usize>],
+    style: Style,
+    highlight: Style,
+) -> Vec<Span<'static>> {
+    let mut spans = vec![];
+    let mut at = range.start;
+    for span in matched {
+        let (start, end) = (span.start.max(at), span.end.min(range.end));
+        if start >= end {
+            continue;
+        }
+        if at < start {
+            spans.push(Span::styled(text[at..start].to_string(), style));
+        }
+        spans.push(Span::styled(text[start..end].to_string(), highlight));
+        at = end;
+    }
+    if at < range.end {
+        spans.push(Span::styled(text[at..range.end].to_string(), style));
+    }
+    spans
+}
+
+/// The type of the entry in one character, like `ls -l` shows it.
+fn type_glyph(entry: &PathEntry) -> char {
+    match entry.special {
+        Some("FIFO") => 'p',
+        Some("Socket") => 's',
+        Some(_) => 'c',
+        None if entry.is_dir => 'd',
+        None => 'f',
+    }
+}
+
+/// A directory heading of the tree view, as tall as the entries around it.
+fn group_item(app: &App, i: usize, entry: &PathEntry, rows: u16) -> ListItem<'static> {
+    let hidden = app.collapsed_count(&entry.pathbuf);
+    let arrow = match (hidden.is_some(), app.config.ascii_mode) {
+        (true, false) => "▸",
+        (false, false) => "▾",
+        (true, true) => "+",
+        (false, true) => "-",
+    };
+    let (marked, total) = app.group_marks(i);
+    let mark_color = match marked {
+        0 => theme(app).muted,
+        _ => theme(app).danger,
+    };
+    let mut heading = vec![
+        Span::raw("  ".repeat(app.tree_depth(entry))),
+        Span::styled(
+            format!("{} {}/", arrow, app.display_path(entry).display()),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(" {}/{} marked", marked, total),
+            Style::default().fg(mark_color),
+        ),
+    ];
+    if let Some(hidden) = hidden {
+        heading.push(Span::styled(
+            format!(" [{} hidden]", hidden),
+            Style::default().fg(theme(app).muted),
+        ));
+    }
+
+    let mut lines = vec![Spans::from(heading)];
+    lines.resize(rows as usize, Spans::default());
+    ListItem::new(lines).style(
+        Style::default()
+            .fg(theme(app).text)
+            .bg(theme(app).background),
+    )
+}
+
+/// The color of the size tier the entry falls in, if any.
+fn size_color(app: &App, entry: &PathEntry) -> Option<Color> {
+    let size = app.entry_size(entry)?;
+    theme(app).configured(app.config.size_color(size).and_then(parse_color))
+}
+
+/// Parses a color name like `red` or `lightyellow`, or `#rrggbb`.
+fn parse_color(name: &str) -> Option<Color> {
+    if let Some(hex) = name.strip_prefix('#') {
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return match hex.len() {
+            6 => Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?)),
+            _ => None,
+        };
+    }
+
+    let color = match name
+        .to_ascii_lowercase()
+        .replace(['_', '-', ' '], "")
+        .as_str()
+    {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        "reset" => Color::Reset,
+        _ => return None,
+    };
+    Some(color)
+}
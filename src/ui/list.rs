@@ -0,0 +1,165 @@
+use std::cmp::Ordering;
+
+/// Items with at most one of them selected.
+pub struct StatefulList<T> {
+    selected: Option<usize>,
+    pub items: Vec<T>,
+}
+
+impl<T> Default for StatefulList<T> {
+    fn default() -> Self {
+        StatefulList::new()
+    }
+}
+
+impl<T> StatefulList<T> {
+    pub fn new() -> Self {
+        StatefulList {
+            selected: None,
+            items: vec![],
+        }
+    }
+
+    pub fn with_items(items: Vec<T>) -> StatefulList<T> {
+        let mut stateful_list = StatefulList {
+            selected: None,
+            items,
+        };
+        if !stateful_list.items.is_empty() {
+            stateful_list.selected = Some(0);
+        }
+        stateful_list
+    }
+
+    pub fn get_index(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Selects the item at `i`, clamped to the last item.
+    pub fn select_index(&mut self, i: usize) {
+        if self.items.is_empty() {
+            self.selected = None;
+            return;
+        }
+        self.selected = Some(i.min(self.items.len() - 1));
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.items.push(item);
+        if self.selected.is_none() {
+            self.selected = Some(0);
+        }
+    }
+
+    /// Inserts `item` after every item that doesn't compare greater, keeping
+    /// the same item selected.
+    pub fn insert_sorted_by<F>(&mut self, item: T, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let i = self
+            .items
+            .partition_point(|other| compare(other, &item) != Ordering::Greater);
+        self.items.insert(i, item);
+
+        match self.selected {
+            Some(selected) if selected >= i && self.items.len() > 1 => {
+                self.selected = Some(selected + 1)
+            }
+            Some(_) => {}
+            None => self.selected = Some(0),
+        }
+    }
+
+    /// Stable sort that keeps the selection at the same position.
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.items.sort_by(compare);
+    }
+
+    /// Keeps the items `keep` returns true for. The selection stays on the
+    /// same item, or moves to the next one kept when it was removed.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let selected = self.selected;
+        let (mut i, mut kept_before) = (0, 0);
+        self.items.retain(|item| {
+            let kept = keep(item);
+            if kept && selected.is_some_and(|selected| i < selected) {
+                kept_before += 1;
+            }
+            i += 1;
+            kept
+        });
+
+        if selected.is_some() {
+            self.select_index(kept_before);
+        }
+    }
+
+    pub fn next(&mut self) {
+        if self.items.is_empty() {
+            self.selected = None;
+            return;
+        }
+
+        let i = match self.selected {
+            Some(i) => {
+                if i >= self.items.len() - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.selected = Some(i);
+    }
+
+    pub fn previous(&mut self) {
+        if self.items.is_empty() {
+            self.selected = None;
+            return;
+        }
+
+        let i = match self.selected {
+            Some(i) => {
+                if i == 0 || i > self.items.len() {
+                    self.items.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.selected = Some(i);
+    }
+
+    /// Moves the selection `n` items down, stopping at the last one.
+    pub fn page_down(&mut self, n: usize) {
+        let i = self.selected.map_or(0, |i| i.saturating_add(n));
+        self.select_index(i);
+    }
+
+    /// Moves the selection `n` items up, stopping at the first one.
+    pub fn page_up(&mut self, n: usize) {
+        let i = self.selected.map_or(0, |i| i.saturating_sub(n));
+        self.select_index(i);
+    }
+
+    pub fn first(&mut self) {
+        self.select_index(0);
+    }
+
+    pub fn last(&mut self) {
+        self.select_index(usize::MAX);
+    }
+
+    pub fn unselect(&mut self) {
+        self.selected = None;
+    }
+}
@@ -0,0 +1,8 @@
+#[cfg(feature = "tui")]
+mod draw;
+pub mod list;
+#[cfg(feature = "tui")]
+pub mod theme;
+
+#[cfg(feature = "tui")]
+pub use draw::*;
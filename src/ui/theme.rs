@@ -0,0 +1,136 @@
+use crate::theme::ThemeName;
+use tui::style::{Color, Modifier, Style};
+
+/// Colors used to draw the interface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub name: ThemeName,
+    pub background: Color,
+    /// Listed entries.
+    pub text: Color,
+    /// Secondary text, like unmarked entries and permissions.
+    pub muted: Color,
+    /// The current pattern line.
+    pub accent: Color,
+    /// Inputs being edited.
+    pub input: Color,
+    /// Status messages and failed entries.
+    pub error: Color,
+    /// Marked entries and destructive choices.
+    pub danger: Color,
+    pub warning: Color,
+    pub success: Color,
+    /// Background of the selected row.
+    pub highlight: Color,
+    /// Text on the mode and dry run badges.
+    pub badge_text: Color,
+    pub file_type: Color,
+    pub size: Color,
+    pub age: Color,
+    pub chart: Color,
+}
+
+impl Theme {
+    pub fn new(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Theme {
+                name,
+                background: Color::Black,
+                text: Color::LightCyan,
+                muted: Color::Gray,
+                accent: Color::Magenta,
+                input: Color::Yellow,
+                error: Color::LightRed,
+                danger: Color::Red,
+                warning: Color::Yellow,
+                success: Color::Green,
+                highlight: Color::DarkGray,
+                badge_text: Color::Black,
+                file_type: Color::LightGreen,
+                size: Color::LightBlue,
+                age: Color::LightMagenta,
+                chart: Color::LightCyan,
+            },
+            ThemeName::Light => Theme {
+                name,
+                background: Color::White,
+                text: Color::Black,
+                muted: Color::DarkGray,
+                accent: Color::Magenta,
+                input: Color::Blue,
+                error: Color::Red,
+                danger: Color::Red,
+                warning: Color::Magenta,
+                success: Color::Green,
+                highlight: Color::Gray,
+                badge_text: Color::White,
+                file_type: Color::Green,
+                size: Color::Blue,
+                age: Color::Magenta,
+                chart: Color::Blue,
+            },
+            ThemeName::HighContrast => Theme {
+                name,
+                background: Color::Black,
+                text: Color::White,
+                muted: Color::White,
+                accent: Color::LightYellow,
+                input: Color::LightYellow,
+                error: Color::LightRed,
+                danger: Color::LightRed,
+                warning: Color::LightYellow,
+                success: Color::LightGreen,
+                highlight: Color::Blue,
+                badge_text: Color::Black,
+                file_type: Color::LightGreen,
+                size: Color::LightCyan,
+                age: Color::LightMagenta,
+                chart: Color::White,
+            },
+            ThemeName::NoColor => Theme {
+                name,
+                background: Color::Reset,
+                text: Color::Reset,
+                muted: Color::Reset,
+                accent: Color::Reset,
+                input: Color::Reset,
+                error: Color::Reset,
+                danger: Color::Reset,
+                warning: Color::Reset,
+                success: Color::Reset,
+                highlight: Color::Reset,
+                badge_text: Color::Reset,
+                file_type: Color::Reset,
+                size: Color::Reset,
+                age: Color::Reset,
+                chart: Color::Reset,
+            },
+        }
+    }
+
+    pub fn is_colored(&self) -> bool {
+        self.name != ThemeName::NoColor
+    }
+
+    /// Text on a `color` background, reversed when colors are off.
+    pub fn badge(&self, color: Color) -> Style {
+        match self.is_colored() {
+            true => Style::default().fg(self.badge_text).bg(color),
+            false => Style::default().add_modifier(Modifier::REVERSED),
+        }
+        .add_modifier(Modifier::BOLD)
+    }
+
+    /// The selected row of a list.
+    pub fn selection(&self) -> Style {
+        match self.is_colored() {
+            true => Style::default().bg(self.highlight),
+            false => Style::default().add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    /// A color from the config, left out when colors are off.
+    pub fn configured(&self, color: Option<Color>) -> Option<Color> {
+        color.filter(|_| self.is_colored())
+    }
+}
@@ -0,0 +1,59 @@
+use crate::ErrorBox;
+use std::path::PathBuf;
+
+/// Puts `paths` back from the trash where they were deleted from. When a path
+/// was trashed more than once, its latest copy is restored.
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+pub fn restore(paths: &[PathBuf]) -> Result<(), ErrorBox> {
+    use std::collections::HashMap;
+    use trash::{os_limited, TrashItem};
+
+    let mut latest: HashMap<PathBuf, TrashItem> = HashMap::new();
+    for item in os_limited::list()? {
+        let path = item.original_path();
+        if !paths.contains(&path) {
+            continue;
+        }
+        match latest.get(&path) {
+            Some(newer) if newer.time_deleted >= item.time_deleted => {}
+            _ => {
+                latest.insert(path, item);
+            }
+        }
+    }
+
+    if latest.len() < paths.len() {
+        return Err(format!(
+            "{} of {} entries are no longer in the trash",
+            paths.len() - latest.len(),
+            paths.len()
+        )
+        .into());
+    }
+    // parents first, restoring a child would create its parent and block it
+    let mut items: Vec<TrashItem> = latest.into_values().collect();
+    items.sort_by_key(|item| item.original_path().components().count());
+    os_limited::restore_all(items)?;
+    Ok(())
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+pub fn restore(_paths: &[PathBuf]) -> Result<(), ErrorBox> {
+    Err("restoring from the trash is not supported on this platform".into())
+}
@@ -0,0 +1,64 @@
+use crate::ErrorBox;
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    path::Path,
+    sync::mpsc::{self, Receiver},
+    time::{Duration, Instant},
+};
+
+/// Bursts of filesystem events closer than this are reported once.
+const DEBOUNCE: Duration = Duration::from_secs(1);
+/// Minimum time between two refreshes caused by the watcher.
+const THROTTLE: Duration = Duration::from_secs(3);
+
+/// Watches the search root for changes that may affect the results.
+pub struct RootWatcher {
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<DebouncedEvent>,
+    pending: bool,
+    last_refresh: Instant,
+}
+
+impl RootWatcher {
+    pub fn new(root: &Path) -> Result<Self, ErrorBox> {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = watcher(sender, DEBOUNCE)?;
+        watcher.watch(root, RecursiveMode::Recursive)?;
+        Ok(RootWatcher {
+            _watcher: watcher,
+            receiver,
+            pending: false,
+            last_refresh: Instant::now(),
+        })
+    }
+
+    /// Whether the results should be refreshed: something changed and the
+    /// last refresh was long enough ago.
+    pub fn refresh_due(&mut self) -> bool {
+        self.pending |= self.has_changed();
+        if self.pending && self.last_refresh.elapsed() >= THROTTLE {
+            self.pending = false;
+            self.last_refresh = Instant::now();
+            return true;
+        }
+        false
+    }
+
+    /// Whether entries were created, removed, renamed or written since the
+    /// last call.
+    fn has_changed(&self) -> bool {
+        // drain everything so old events don't trigger another refresh later
+        let mut changed = false;
+        for event in self.receiver.try_iter() {
+            changed |= matches!(
+                event,
+                DebouncedEvent::Create(_)
+                    | DebouncedEvent::Remove(_)
+                    | DebouncedEvent::Rename(_, _)
+                    | DebouncedEvent::Write(_)
+                    | DebouncedEvent::Rescan
+            );
+        }
+        changed
+    }
+}
@@ -0,0 +1,116 @@
+use eradicate_core::{archive, App, PathEntry, StatefulList};
+use flate2::read::GzDecoder;
+use std::{fs, fs::File, path::PathBuf};
+
+/// The paths in the tarball at `path`, sorted.
+fn archived_names(path: &std::path::Path) -> Vec<PathBuf> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(path).unwrap()));
+    let mut names: Vec<PathBuf> = archive
+        .entries()
+        .unwrap()
+        .map(|entry| entry.unwrap().path().unwrap().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn archives_keep_the_paths_below_the_root() {
+    let root = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+    let build = root.path().join("build");
+    fs::create_dir(&build).unwrap();
+    fs::write(build.join("app.o"), "o").unwrap();
+    fs::write(root.path().join("a.log"), "a").unwrap();
+
+    let paths = vec![build, root.path().join("a.log")];
+    let dest = out.path().join("junk.tar.gz");
+    archive::write(&paths, root.path(), &dest).unwrap();
+    archive::verify(&dest, &paths, root.path()).unwrap();
+
+    assert_eq!(
+        archived_names(&dest),
+        [
+            PathBuf::from("a.log"),
+            PathBuf::from("build"),
+            PathBuf::from("build/app.o")
+        ]
+    );
+    let missing = vec![root.path().join("other.log")];
+    assert!(archive::verify(&dest, &missing, root.path()).is_err());
+}
+
+#[test]
+fn marked_entries_are_deleted_once_archived() {
+    let root = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+    let file = root.path().join("a.log");
+    fs::write(&file, "a").unwrap();
+
+    let mut app = App::new();
+    app.root = root.path().to_path_buf();
+    app.config.archive_dir = Some(out.path().to_path_buf());
+    app.config.log_deletions = false;
+    app.config.permanent_delete = true;
+    app.list = StatefulList::with_items(vec![PathEntry::new(file.clone())]);
+    app.archive_marked().unwrap();
+    app.wait_for_archive().unwrap();
+    app.wait_for_delete();
+
+    assert!(!file.exists());
+    let archives: Vec<PathBuf> = fs::read_dir(out.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(archives.len(), 1);
+    assert_eq!(archived_names(&archives[0]), [PathBuf::from("a.log")]);
+    let popup = app.popup.as_ref().unwrap();
+    assert!(popup.lines[3].starts_with("Archived to "));
+}
+
+#[test]
+fn nothing_is_deleted_when_archiving_fails() {
+    let root = tempfile::tempdir().unwrap();
+    let file = root.path().join("a.log");
+    fs::write(&file, "a").unwrap();
+    // a file where the archive directory should be
+    let blocker = root.path().join("not-a-dir");
+    fs::write(&blocker, "").unwrap();
+
+    let mut app = App::new();
+    app.root = root.path().to_path_buf();
+    app.config.archive_dir = Some(blocker);
+    app.list = StatefulList::with_items(vec![PathEntry::new(file.clone())]);
+    app.archive_marked().unwrap();
+    app.wait_for_archive().unwrap();
+
+    assert!(file.exists());
+    assert!(!app.is_deleting());
+    assert!(app.status.unwrap().starts_with("could not archive"));
+}
+
+#[test]
+fn only_the_archived_entries_are_deleted() {
+    let root = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+    let file = root.path().join("a.log");
+    let late = root.path().join("b.log");
+    fs::write(&file, "a").unwrap();
+    fs::write(&late, "b").unwrap();
+
+    let mut app = App::new();
+    app.root = root.path().to_path_buf();
+    app.config.archive_dir = Some(out.path().to_path_buf());
+    app.config.log_deletions = false;
+    app.config.permanent_delete = true;
+    app.list = StatefulList::with_items(vec![PathEntry::new(file.clone())]);
+    app.archive_marked().unwrap();
+    // listed and marked while the archive is written
+    app.push_entry(PathEntry::new(late.clone()));
+    app.wait_for_archive().unwrap();
+    app.wait_for_delete();
+
+    assert!(!file.exists());
+    assert!(late.exists());
+    assert_eq!(app.list.items.len(), 1);
+}
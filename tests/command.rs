@@ -0,0 +1,119 @@
+use eradicate_core::{App, PathEntry, StatefulList};
+use std::path::PathBuf;
+
+fn app_with(paths: &[&str]) -> App {
+    let mut app = App::new();
+    let entries = paths
+        .iter()
+        .map(|p| PathEntry::new(PathBuf::from(p)))
+        .collect();
+    app.list = StatefulList::with_items(entries);
+    app
+}
+
+#[cfg(unix)]
+#[test]
+fn commands_get_the_marked_paths_as_arguments() {
+    let mut app = app_with(&["/tmp/a b.log", "/tmp/c.log", "/tmp/d.log"]);
+    app.list.select_index(2);
+    app.toggle_delete();
+
+    app.run_command("printf '%s\\n'");
+    assert!(app.is_busy());
+    app.wait_for_command();
+
+    assert!(!app.is_busy());
+    assert_eq!(
+        app.command_log,
+        ["$ printf '%s\\n' (2 entries)", "/tmp/a b.log", "/tmp/c.log"]
+    );
+    assert_eq!(
+        app.status.as_deref(),
+        Some("ran printf '%s\\n' on 2 entries")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn failing_commands_are_reported() {
+    let mut app = app_with(&["/tmp/a.log"]);
+
+    app.run_command("echo oops >&2; false");
+    app.wait_for_command();
+
+    assert_eq!(app.command_log[1], "oops");
+    assert!(app.command_log[2].contains("exited with"));
+    assert_eq!(
+        app.status.as_deref(),
+        Some("echo oops >&2; false failed, see its output")
+    );
+}
+
+#[test]
+fn nothing_runs_without_marked_entries() {
+    let mut app = app_with(&["/tmp/a.log"]);
+    app.unmark_all();
+
+    app.run_command("echo");
+    assert!(!app.is_busy());
+    assert!(app.command_log.is_empty());
+    assert!(app.status.is_some());
+}
+
+#[test]
+fn the_delete_command_quotes_every_marked_path() {
+    let app = app_with(&["/tmp/it's here.log", "-rf", "/tmp/plain.log"]);
+
+    assert_eq!(
+        app.generate_delete_command().unwrap(),
+        r"rm -rf -- '/tmp/it'\''s here.log' -rf /tmp/plain.log"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn the_shell_reads_back_the_quoted_paths() {
+    use std::process::Command;
+
+    let paths = [
+        "/tmp/it's here.log",
+        "-rf",
+        "/tmp/a\"b $c `d`.log",
+        "/tmp/tab\t.log",
+    ];
+    let app = app_with(&paths);
+
+    let command = app.generate_delete_command().unwrap();
+    let args = command.strip_prefix("rm -rf -- ").unwrap();
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(format!("printf '%s\\n' {}", args))
+        .output()
+        .unwrap();
+    let expected: String = paths.iter().map(|p| format!("{}\n", p)).collect();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn no_delete_command_without_marked_entries() {
+    let mut app = app_with(&["/tmp/a.log"]);
+    app.unmark_all();
+
+    assert!(app.generate_delete_command().is_err());
+    app.show_delete_command();
+    assert!(app.popup.is_none());
+    assert!(app.status.is_some());
+}
+
+#[cfg(unix)]
+#[test]
+fn paths_that_are_not_utf8_get_no_delete_command() {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    let mut app = app_with(&[]);
+    app.list = StatefulList::with_items(vec![PathEntry::new(PathBuf::from(OsStr::from_bytes(
+        b"/tmp/bad\xff.log",
+    )))]);
+
+    assert!(app.generate_delete_command().is_err());
+}
@@ -0,0 +1,85 @@
+use eradicate_core::{
+    config::{Column, Config, Density, MIN_TICK_RATE},
+    presets::{self, Preset},
+};
+
+#[test]
+fn config_round_trips_through_toml() {
+    let config = Config {
+        columns: vec![Column::Size, Column::Mtime, Column::Path],
+        density: Density::Dense,
+        split_ratio: 35,
+        show_help: false,
+        show_histogram: true,
+        presets: vec![Preset::new("logs", "**/*.log")],
+        ..Config::default()
+    };
+
+    let content = config.to_toml().unwrap();
+    let parsed: Config = toml::from_str(&content).unwrap();
+
+    assert_eq!(parsed, config);
+}
+
+#[test]
+fn missing_fields_fall_back_to_defaults() {
+    let parsed: Config = toml::from_str("split_ratio = 70").unwrap();
+
+    assert_eq!(parsed.split_ratio, 70);
+    assert_eq!(parsed.columns, Config::default().columns);
+    assert_eq!(parsed.density, Density::Comfortable);
+    assert!(parsed.show_help);
+}
+
+#[test]
+fn partial_keymap_keeps_the_other_default_keys() {
+    let parsed: Config = toml::from_str("tick_rate = 100\n[keymap]\nquit = [\"Q\"]").unwrap();
+
+    assert_eq!(parsed.tick_rate, 100);
+    assert_eq!(parsed.keymap.quit, vec![String::from("Q")]);
+    assert_eq!(parsed.keymap.down, Config::default().keymap.down);
+}
+
+#[test]
+fn default_confirm_keys_share_nothing_with_toggle() {
+    let keymap = Config::default().keymap;
+
+    assert_eq!(keymap.confirm, vec![String::from("y")]);
+    assert!(keymap
+        .toggle
+        .iter()
+        .all(|key| !keymap.confirm.contains(key)));
+}
+
+#[test]
+fn tick_rate_is_kept_above_the_minimum() {
+    let parsed: Config = toml::from_str("tick_rate = 0").unwrap();
+
+    assert_eq!(parsed.tick_rate, MIN_TICK_RATE);
+}
+
+#[test]
+fn preset_rules_are_written_into_the_pattern() {
+    let content = r#"
+[[presets]]
+name = "build-junk"
+pattern = "**/target **/dist"
+excludes = ["**/keep/**"]
+min_age = "30d"
+min_size = "10M"
+"#;
+    let parsed: Config = toml::from_str(content).unwrap();
+
+    let preset = presets::find(&parsed, "build-junk").unwrap();
+    assert_eq!(
+        preset.search_pattern(),
+        "**/target **/dist !**/keep/** +mtime:30d size>10M"
+    );
+    assert!(presets::find(&parsed, "missing").is_none());
+    assert_eq!(
+        presets::find(&parsed, "node_modules")
+            .unwrap()
+            .search_pattern(),
+        "**/node_modules !**/node_modules/**"
+    );
+}
@@ -0,0 +1,269 @@
+use eradicate_core::{App, ConfirmAction, MarkView, PathEntry, StatefulList};
+use std::{fs, path::PathBuf};
+
+#[test]
+fn nested_marked_paths_go_with_their_topmost_parent() {
+    let dir = tempfile::tempdir().unwrap();
+    let parent = dir.path().join("build");
+    let child = parent.join("cache");
+    let file = child.join("out.o");
+    fs::create_dir_all(&child).unwrap();
+    fs::write(&file, "o").unwrap();
+
+    // parents listed before their children, as a search would find them
+    let mut app = App::new();
+    app.list = StatefulList::with_items(vec![
+        PathEntry::new(parent.clone()),
+        PathEntry::new(child.clone()),
+        PathEntry::new(file.clone()),
+    ]);
+    let covered = app.covered_paths();
+    assert_eq!(covered.len(), 2);
+    assert!(covered.contains(child.as_path()) && covered.contains(file.as_path()));
+    assert_eq!(app.entries_to_delete().len(), 1);
+
+    app.config.log_deletions = false;
+    app.delete_active_entries(true).unwrap();
+    app.wait_for_delete();
+
+    assert!(!parent.exists());
+    assert!(app.list.items.is_empty());
+    assert_eq!(app.status.as_deref(), Some("eradicated 1 entries"));
+}
+
+#[test]
+fn protected_paths_are_never_marked_or_deleted() {
+    let dir = tempfile::tempdir().unwrap();
+    let git = dir.path().join(".git");
+    let kept = dir.path().join("kept");
+    fs::create_dir(&git).unwrap();
+    fs::create_dir(&kept).unwrap();
+
+    let mut app = App::new();
+    app.config.protected_paths = vec![kept.clone()];
+    app.push_entry(PathEntry::new(git.clone()));
+    app.push_entry(PathEntry::new(kept.clone()));
+    assert!(app.list.items.iter().all(|e| !e.is_delete()));
+
+    app.mark_all();
+    assert!(app.list.items.iter().all(|e| !e.is_delete()));
+    assert!(app.popup.is_some());
+
+    app.delete_active_entries(true).unwrap();
+    assert!(git.exists() && kept.exists());
+}
+
+#[test]
+fn protected_paths_are_found_through_parent_dirs() {
+    let dir = tempfile::tempdir().unwrap();
+    let kept = dir.path().join("kept");
+    fs::create_dir(dir.path().join("proj")).unwrap();
+    fs::create_dir(&kept).unwrap();
+
+    // `proj/..` lists the directory holding `kept`
+    let mut app = App::new();
+    app.config.protected_paths = vec![kept.clone()];
+    app.root = dir.path().join("proj").join("..");
+    app.pattern.content = String::from("*");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+
+    let entry = app
+        .list
+        .items
+        .iter()
+        .find(|entry| entry.pathbuf.ends_with("kept"))
+        .unwrap();
+    assert!(app.protection(entry).is_some());
+    assert!(!entry.is_delete());
+
+    app.mark_all();
+    app.delete_active_entries(true).unwrap();
+    assert!(kept.exists());
+}
+
+#[test]
+fn failed_entries_are_kept_with_their_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let gone = dir.path().join("gone.tmp");
+    let other = dir.path().join("other.tmp");
+    fs::write(&gone, "g").unwrap();
+    fs::write(&other, "o").unwrap();
+
+    let mut app = App::new();
+    app.config.log_deletions = false;
+    app.list = StatefulList::with_items(vec![
+        PathEntry::new(gone.clone()),
+        PathEntry::new(other.clone()),
+    ]);
+    fs::remove_file(&gone).unwrap();
+    app.delete_active_entries(true).unwrap();
+    app.wait_for_delete();
+
+    assert!(!other.exists());
+    assert_eq!(app.list.items.len(), 1);
+    assert_eq!(app.list.items[0].pathbuf, gone);
+    assert!(app.list.items[0].error.is_some());
+
+    let popup = app.popup.as_ref().unwrap();
+    assert_eq!(popup.title, "Deletion summary");
+    assert_eq!(popup.lines[0], "Eradicated 1 of 2 entries, 1B");
+    assert!(popup.lines[1].starts_with("Took "));
+    assert_eq!(popup.lines[2], "Deleted for good");
+    let failure = format!("Failed: {}: ", gone.display());
+    assert!(popup.lines.iter().any(|line| line.starts_with(&failure)));
+}
+
+#[test]
+fn interrupting_stops_the_batch_and_reports_what_is_left() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("a.tmp");
+    let kept = dir.path().join("cache");
+    fs::write(&file, "a").unwrap();
+    fs::create_dir(&kept).unwrap();
+
+    // the batch pauses on the directory, waiting for a confirmation
+    let mut app = App::new();
+    app.config.per_dir_confirm = true;
+    app.config.log_deletions = false;
+    app.list = StatefulList::with_items(vec![
+        PathEntry::new(file.clone()),
+        PathEntry::new(kept.clone()),
+    ]);
+    app.delete_active_entries(true).unwrap();
+    app.wait_for_delete();
+    assert!(app.confirm.is_some());
+
+    app.interrupt();
+    assert!(app.should_quit);
+    assert!(app.confirm.is_none());
+    assert!(!file.exists() && kept.exists());
+    assert_eq!(
+        app.exit_report,
+        [
+            "Interrupted while deleting",
+            "Eradicated 1 of 2 entries, 1B",
+            "Left untouched: 1 entries",
+        ]
+    );
+}
+
+#[test]
+fn the_pattern_runs_again_after_deleting() {
+    let dir = tempfile::tempdir().unwrap();
+    let (a, b) = (dir.path().join("a.tmp"), dir.path().join("b.tmp"));
+    fs::write(&a, "a").unwrap();
+    fs::write(&b, "b").unwrap();
+
+    let mut app = App::new();
+    app.config.log_deletions = false;
+    app.root = dir.path().to_path_buf();
+    app.pattern.content = String::from("*.tmp");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+    app.list.items.sort_by(|x, y| x.pathbuf.cmp(&y.pathbuf));
+    app.list.select_index(1);
+    app.toggle_delete();
+
+    // shows up between the search and the deletion
+    let c = dir.path().join("c.tmp");
+    fs::write(&c, "c").unwrap();
+    app.delete_active_entries(true).unwrap();
+    app.wait_for_delete();
+    app.wait_for_search();
+
+    let mut paths: Vec<_> = app.list.items.iter().map(|e| e.pathbuf.clone()).collect();
+    paths.sort();
+    assert_eq!(paths, [b.clone(), c]);
+    assert!(!a.exists());
+    assert_eq!(app.selected_path(), Some(b.as_path()));
+}
+
+#[test]
+fn run_overrides_are_kept_out_of_the_config() {
+    let mut app = App::new();
+    app.force_permanent = true;
+    app.skip_log = true;
+    app.force_quarantine = true;
+    app.push_entry(PathEntry::new(PathBuf::from("a.log")));
+    app.request_delete(app.permanent_delete());
+
+    assert!(matches!(
+        app.confirm,
+        Some(ConfirmAction::Delete {
+            permanent: true,
+            ..
+        })
+    ));
+    assert!(!app.config.permanent_delete);
+    assert!(!app.log_deletions() && app.config.log_deletions);
+    assert!(app.quarantine() && !app.config.quarantine);
+}
+
+#[test]
+fn refreshing_after_a_deletion_keeps_marks_and_views() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["a.log", "b.log", "c.log", "keep.log"] {
+        fs::write(dir.path().join(name), name).unwrap();
+    }
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.config.log_deletions = false;
+    app.pattern.content = String::from("*.log");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+    for entry in app.list.items.iter_mut() {
+        entry.set_delete(entry.pathbuf.ends_with("a.log"));
+    }
+    app.delete_active_entries(true).unwrap();
+    app.wait_for_delete();
+    app.wait_for_search();
+
+    // listed again, still unmarked
+    assert_eq!(app.list.items.len(), 3);
+    assert!(app.list.items.iter().all(|e| !e.is_delete()));
+
+    // a filtered list stays filtered
+    app.set_filter("b.log");
+    app.list.items[0].set_delete(true);
+    app.delete_active_entries(true).unwrap();
+    app.wait_for_delete();
+    assert!(!app.is_searching());
+    assert_eq!(app.filter, "b.log");
+    assert!(app.list.items.is_empty());
+    assert!(dir.path().join("c.log").exists());
+}
+
+#[test]
+fn entries_inside_a_deleted_directory_stay_gone_once_shown_again() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = dir.path().join("cache");
+    fs::create_dir(&cache).unwrap();
+    fs::write(cache.join("x.log"), "x").unwrap();
+    fs::write(dir.path().join("keep.log"), "k").unwrap();
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.config.log_deletions = false;
+    app.list = StatefulList::with_items(vec![
+        PathEntry::new(cache.clone()),
+        PathEntry::new(cache.join("x.log")),
+        PathEntry::new(dir.path().join("keep.log")),
+    ]);
+    for entry in app.list.items.iter_mut() {
+        entry.set_delete(entry.pathbuf == cache);
+    }
+    // only the directory is shown
+    app.cycle_mark_view();
+    assert_eq!(app.list.items.len(), 1);
+    app.delete_active_entries(true).unwrap();
+    app.wait_for_delete();
+    assert!(!cache.exists());
+
+    while app.mark_view != MarkView::All {
+        app.cycle_mark_view();
+    }
+    let paths: Vec<_> = app.list.items.iter().map(|e| e.pathbuf.clone()).collect();
+    assert_eq!(paths, vec![dir.path().join("keep.log")]);
+}
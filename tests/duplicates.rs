@@ -0,0 +1,61 @@
+use eradicate_core::{duplicates, App, PathEntry, StatefulList};
+use std::fs;
+
+#[test]
+fn only_files_with_the_same_bytes_are_grouped() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths: Vec<_> = ["a", "b", "c", "d", "empty1", "empty2"]
+        .iter()
+        .map(|name| dir.path().join(name))
+        .collect();
+    fs::write(&paths[0], "same").unwrap();
+    fs::write(&paths[1], "same").unwrap();
+    // same size, other bytes
+    fs::write(&paths[2], "diff").unwrap();
+    fs::write(&paths[3], "same").unwrap();
+    fs::write(&paths[4], "").unwrap();
+    fs::write(&paths[5], "").unwrap();
+
+    let groups = duplicates::find(paths.clone());
+    assert_eq!(
+        groups,
+        vec![vec![paths[0].clone(), paths[1].clone(), paths[3].clone()]]
+    );
+}
+
+#[test]
+fn every_copy_but_the_first_is_marked() {
+    let dir = tempfile::tempdir().unwrap();
+    let names = ["x1", "x2", "y1", "y2", "z"];
+    for name in names {
+        fs::write(dir.path().join(name), &name[..1]).unwrap();
+    }
+
+    let mut app = App::new();
+    app.list = StatefulList::with_items(
+        names
+            .iter()
+            .map(|name| PathEntry::new(dir.path().join(name)))
+            .collect(),
+    );
+    app.unmark_all();
+    app.find_duplicates();
+    app.wait_for_duplicates();
+
+    let listed: Vec<_> = app
+        .list
+        .items
+        .iter()
+        .map(|e| (e.pathbuf.file_name().unwrap().to_owned(), e.is_delete()))
+        .collect();
+    assert_eq!(
+        listed,
+        vec![
+            ("x1".into(), false),
+            ("x2".into(), true),
+            ("y1".into(), false),
+            ("y2".into(), true),
+        ]
+    );
+    assert_eq!(app.duplicate_groups[&dir.path().join("y2")], 2);
+}
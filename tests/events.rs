@@ -0,0 +1,313 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use eradicate_core::{
+    config::Density, event::AppEvent, humanize::truncate_path, ui, App, AppMode, ConfirmAction,
+    PathEntry, StatefulList,
+};
+use std::path::PathBuf;
+use tui::{backend::TestBackend, Terminal};
+
+fn app_with(paths: &[&str]) -> App {
+    let mut app = App::new();
+    let entries = paths
+        .iter()
+        .map(|p| PathEntry::new(PathBuf::from(p)))
+        .collect();
+    app.list = StatefulList::with_items(entries);
+    app
+}
+
+/// Presses each key in turn, returns whether the last one asked for a redraw.
+fn press(app: &mut App, codes: &[KeyCode]) -> bool {
+    codes.iter().fold(false, |_, code| {
+        let key = KeyEvent::new(*code, KeyModifiers::NONE);
+        app.handle_event(AppEvent::Input(Event::Key(key))).unwrap()
+    })
+}
+
+fn chars(s: &str) -> Vec<KeyCode> {
+    s.chars().map(KeyCode::Char).collect()
+}
+
+/// The screen as drawn for `app`, one line per row.
+fn render(app: &mut App) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    terminal.draw(|f| ui::draw(f, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let width = buffer.area.width as usize;
+    buffer
+        .content
+        .chunks(width)
+        .map(|row| {
+            row.iter()
+                .map(|cell| cell.symbol.as_str())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn motions_move_the_selection_with_counts() {
+    let mut app = app_with(&["a.log", "b.log", "c.log", "d.log", "e.log"]);
+    app.list.select_index(0);
+
+    assert!(press(&mut app, &chars("j")));
+    assert_eq!(app.list.get_index(), Some(1));
+    press(&mut app, &chars("3j"));
+    assert_eq!(app.list.get_index(), Some(4));
+    press(&mut app, &[KeyCode::Up]);
+    assert_eq!(app.list.get_index(), Some(3));
+    press(&mut app, &chars("gg"));
+    assert_eq!(app.list.get_index(), Some(0));
+    press(&mut app, &chars("G"));
+    assert_eq!(app.list.get_index(), Some(4));
+}
+
+#[test]
+fn dd_asks_to_delete_only_the_selected_entries() {
+    let mut app = app_with(&["a.log", "b.log", "c.log", "d.log"]);
+    app.list.select_index(1);
+
+    // everything starts marked, only the two from the selection go
+    press(&mut app, &chars("2dd"));
+    assert!(matches!(app.app_mode, AppMode::Confirm));
+    match &app.confirm {
+        Some(ConfirmAction::Delete { count, only, .. }) => {
+            assert_eq!(*count, 2);
+            let only = only.as_ref().unwrap();
+            assert!(only.contains(&PathBuf::from("b.log")));
+            assert!(only.contains(&PathBuf::from("c.log")));
+        }
+        _ => panic!("no deletion to confirm"),
+    }
+    press(&mut app, &chars("n"));
+
+    // the first d asks about the marked entries, the second about the
+    // selected one alone
+    app.list.select_index(3);
+    press(&mut app, &chars("d"));
+    assert!(matches!(
+        app.confirm,
+        Some(ConfirmAction::Delete {
+            count: 4,
+            only: None,
+            ..
+        })
+    ));
+    press(&mut app, &chars("d"));
+    assert!(matches!(
+        app.confirm,
+        Some(ConfirmAction::Delete { count: 1, .. })
+    ));
+}
+
+#[test]
+fn enter_toggles_the_selected_entry() {
+    let mut app = app_with(&["a.log", "b.log"]);
+    app.list.select_index(1);
+
+    press(&mut app, &[KeyCode::Enter]);
+    assert!(app.list.items[0].is_delete());
+    assert!(!app.list.items[1].is_delete());
+    press(&mut app, &[KeyCode::Enter]);
+    assert!(app.list.items[1].is_delete());
+}
+
+#[test]
+fn typing_in_insert_mode_edits_the_pattern() {
+    let mut app = App::new();
+
+    press(&mut app, &chars("i"));
+    assert!(matches!(app.app_mode, AppMode::Insert));
+    press(&mut app, &chars("*.lgo"));
+    press(&mut app, &[KeyCode::Backspace, KeyCode::Backspace]);
+    press(&mut app, &chars("og"));
+    assert_eq!(app.pattern.content, "*.log");
+
+    press(&mut app, &[KeyCode::Esc]);
+    assert!(matches!(app.app_mode, AppMode::Normal));
+    // typed keys don't reach the pattern in normal mode
+    press(&mut app, &chars("j"));
+    assert_eq!(app.pattern.content, "*.log");
+}
+
+#[test]
+fn any_key_closes_a_popup_first() {
+    let mut app = app_with(&["a.log", "b.log"]);
+    app.list.select_index(0);
+    app.show_inspector();
+    assert!(app.popup.is_some());
+
+    press(&mut app, &chars("j"));
+    assert!(app.popup.is_none());
+    assert_eq!(app.list.get_index(), Some(0));
+}
+
+#[test]
+fn idle_ticks_and_mouse_moves_skip_the_redraw() {
+    let mut app = app_with(&["a.log"]);
+    let moved = MouseEvent {
+        kind: MouseEventKind::Moved,
+        column: 3,
+        row: 3,
+        modifiers: KeyModifiers::NONE,
+    };
+
+    assert!(!app.handle_event(AppEvent::Tick).unwrap());
+    assert!(!app
+        .handle_event(AppEvent::Input(Event::Mouse(moved)))
+        .unwrap());
+    assert!(app
+        .handle_event(AppEvent::Input(Event::Resize(80, 24)))
+        .unwrap());
+}
+
+#[test]
+fn visual_mode_marks_the_range_as_the_selection_moves() {
+    let mut app = app_with(&["a.log", "b.log", "c.log", "d.log", "e.log"]);
+    app.unmark_all();
+    app.list.items[4].toggle_delete();
+    app.list.select_index(1);
+    let marks = |app: &App| -> Vec<bool> { app.list.items.iter().map(|e| e.is_delete()).collect() };
+
+    press(&mut app, &chars("V2j"));
+    assert!(matches!(app.app_mode, AppMode::Visual));
+    assert_eq!(marks(&app), [false, true, true, true, true]);
+    // going back up puts back what the range left
+    press(&mut app, &chars("3k"));
+    assert_eq!(marks(&app), [true, true, false, false, true]);
+    assert!(render(&mut app).contains("VISUAL"));
+
+    press(&mut app, &[KeyCode::Esc]);
+    assert!(matches!(app.app_mode, AppMode::Normal));
+    assert_eq!(marks(&app), [false, false, false, false, true]);
+
+    press(&mut app, &chars("VG"));
+    press(&mut app, &[KeyCode::Enter]);
+    assert!(matches!(app.app_mode, AppMode::Normal));
+    assert_eq!(marks(&app), [true, true, true, true, true]);
+}
+
+#[test]
+fn large_result_sets_are_drawn_compact() {
+    let paths: Vec<String> = (0..150).map(|i| format!("{}.log", i)).collect();
+    let mut app = app_with(&paths.iter().map(String::as_str).collect::<Vec<_>>());
+    app.config.compact_above = 100;
+    app.list.select_index(0);
+    assert_eq!(app.density(), Density::Compact);
+
+    let screen = render(&mut app);
+    assert_eq!(app.rows_per_entry, 1);
+    assert!(screen.contains("o f     0B 0.log"));
+
+    app.config.compact_above = 0;
+    assert_eq!(app.density(), Density::Comfortable);
+    render(&mut app);
+    assert_eq!(app.rows_per_entry, 3);
+}
+
+#[test]
+fn long_paths_are_cut_in_the_middle() {
+    assert_eq!(truncate_path("/home/me/a.log", 20, "…"), "/home/me/a.log");
+    assert_eq!(
+        truncate_path("/home/me/projects/a.log", 14, "…"),
+        "/home/m…/a.log"
+    );
+    assert_eq!(
+        truncate_path("/srv/a-very-long-name.log", 10, "..."),
+        "...ame.log"
+    );
+    assert_eq!(
+        truncate_path("/srv/naïve/日本語.txt", 14, "…"),
+        "/s…/日本語.txt"
+    );
+
+    let long = format!("/{}/the-file.log", "deep/".repeat(40));
+    let mut app = app_with(&[long.as_str()]);
+    app.list.select_index(0);
+    let screen = render(&mut app);
+    assert!(screen.contains("/deep/deep/"));
+    assert!(screen.contains("…/the-file.log"));
+}
+
+#[test]
+fn the_command_line_runs_ex_commands() {
+    let mut app = app_with(&["a.log", "b.txt", "c.log"]);
+    app.list.select_index(0);
+
+    press(&mut app, &chars(":filter *.log"));
+    assert!(matches!(app.app_mode, AppMode::Command));
+    assert!(render(&mut app).contains(":filter *.log"));
+    press(&mut app, &[KeyCode::Enter]);
+    assert!(matches!(app.app_mode, AppMode::Normal));
+    assert_eq!(app.list.items.len(), 2);
+
+    press(&mut app, &chars(":sort! name"));
+    press(&mut app, &[KeyCode::Enter]);
+    assert_eq!(app.list.items[0].pathbuf, PathBuf::from("c.log"));
+
+    press(&mut app, &chars(":frobnicate"));
+    press(&mut app, &[KeyCode::Enter]);
+    assert_eq!(app.status.as_deref(), Some("not a command: frobnicate"));
+
+    // erasing past the start leaves the command line
+    press(&mut app, &chars(":"));
+    press(&mut app, &[KeyCode::Backspace]);
+    assert!(matches!(app.app_mode, AppMode::Normal));
+
+    press(&mut app, &chars(":q!"));
+    press(&mut app, &[KeyCode::Enter]);
+    assert!(app.should_quit);
+}
+
+#[test]
+fn the_screen_shows_the_mode_and_entries() {
+    let mut app = app_with(&["first.log", "second.log"]);
+    app.list.select_index(0);
+
+    let screen = render(&mut app);
+    assert!(screen.contains("NORMAL"));
+    assert!(screen.contains("first.log"));
+    assert!(screen.contains("second.log"));
+
+    press(&mut app, &chars("i"));
+    let screen = render(&mut app);
+    assert!(screen.contains("INSERT"));
+}
+
+#[test]
+fn clicks_land_on_the_drawn_rows() {
+    let mut app = app_with(&["first.log", "second.log", "third.log"]);
+    app.list.select_index(0);
+    render(&mut app);
+
+    let click = MouseEvent {
+        kind: MouseEventKind::Down(crossterm::event::MouseButton::Left),
+        column: app.list_area.right() - 1,
+        row: app.list_area.y + 2 * app.rows_per_entry,
+        modifiers: KeyModifiers::NONE,
+    };
+    app.handle_event(AppEvent::Input(Event::Mouse(click)))
+        .unwrap();
+    assert_eq!(app.list.get_index(), Some(2));
+}
+
+#[test]
+fn the_size_to_delete_is_shown_once_directories_are_added_up() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.log"), "abc").unwrap();
+    let mut app = App::new();
+    app.push_entry(PathEntry::new(dir.path().to_path_buf()));
+
+    press(&mut app, &chars("B"));
+    assert!(render(&mut app).contains("Archive 1 marked entries (calculating…)"));
+    press(&mut app, &chars("n"));
+    press(&mut app, &chars("d"));
+    assert!(render(&mut app).contains("calculating…"));
+
+    while app.is_busy() {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        app.poll_sizes();
+    }
+    assert!(render(&mut app).contains("entries (3B) to the trash?"));
+}
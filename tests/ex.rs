@@ -0,0 +1,54 @@
+use eradicate_core::{ex::ExCommand, SortKey};
+use std::path::PathBuf;
+
+fn parse(line: &str) -> ExCommand {
+    ExCommand::parse(line).unwrap().unwrap()
+}
+
+#[test]
+fn commands_parse_with_their_arguments() {
+    assert_eq!(
+        parse("sort size"),
+        ExCommand::Sort {
+            key: Some(SortKey::Size),
+            descending: false
+        }
+    );
+    assert_eq!(
+        parse("so! none"),
+        ExCommand::Sort {
+            key: None,
+            descending: true
+        }
+    );
+    assert_eq!(
+        parse("filter *.log"),
+        ExCommand::Filter(String::from("*.log"))
+    );
+    assert_eq!(parse("f"), ExCommand::Filter(String::new()));
+    assert_eq!(parse("delete!"), ExCommand::Delete { force: true });
+    assert_eq!(
+        parse("cd ../other dir"),
+        ExCommand::Cd(Some(PathBuf::from("../other dir")))
+    );
+    assert_eq!(parse("cd"), ExCommand::Cd(None));
+    assert_eq!(
+        parse("w export.txt"),
+        ExCommand::Write(PathBuf::from("export.txt"))
+    );
+    assert_eq!(parse("  quit "), ExCommand::Quit { force: false });
+    assert_eq!(ExCommand::parse("").unwrap(), None);
+}
+
+#[test]
+fn mistakes_are_explained() {
+    let error = |line: &str| ExCommand::parse(line).unwrap_err().to_string();
+    assert_eq!(error("s size"), "not a command: s");
+    assert_eq!(
+        error("sort color"),
+        "sort by name, size, mtime or type, or none for the search order"
+    );
+    assert_eq!(error("w"), "write needs a file name");
+    assert_eq!(error("cd! /"), "cd takes no !");
+    assert_eq!(error("q now"), "quit takes no argument");
+}
@@ -0,0 +1,77 @@
+use eradicate_core::{
+    export::{self, Format},
+    App,
+};
+use std::{fs, path::PathBuf};
+
+#[test]
+fn path_lists_skip_blank_lines_and_repeats() {
+    let paths = export::parse_paths("a/b\r\n\n  \nc d\na/b\n");
+    assert_eq!(paths, [PathBuf::from("a/b"), PathBuf::from("c d")]);
+}
+
+#[test]
+fn imported_paths_replace_the_results() {
+    let dir = tempfile::tempdir().unwrap();
+    let (a, b) = (dir.path().join("a.log"), dir.path().join("b"));
+    fs::write(&a, "abc").unwrap();
+    fs::create_dir(&b).unwrap();
+
+    let mut app = App::new();
+    app.load_paths(vec![a.clone(), b.clone(), dir.path().join("gone")]);
+    assert_eq!(app.list.items.len(), 2);
+    assert_eq!(app.status.as_deref(), Some("loaded 2 paths, 1 don't exist"));
+
+    app.list.select_index(1);
+    app.toggle_delete();
+    let marked = app.exported_entries(true);
+    assert_eq!(marked.len(), 1);
+    assert_eq!(marked[0].path, a);
+    assert_eq!(marked[0].kind, "file");
+    assert_eq!(marked[0].size, Some(3));
+
+    let plain = dir.path().join("out.txt");
+    app.export_to(plain.to_str().unwrap(), false);
+    let text = fs::read_to_string(&plain).unwrap();
+    assert_eq!(export::parse_paths(&text), [a.clone(), b.clone()]);
+
+    let json = dir.path().join("out.json");
+    app.export_to(json.to_str().unwrap(), true);
+    let value: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&json).unwrap()).unwrap();
+    assert_eq!(value[0]["path"], a.to_str().unwrap());
+    assert_eq!(value[0]["type"], "file");
+    assert_eq!(value[0]["marked"], true);
+    assert_eq!(value.as_array().unwrap().len(), 1);
+    assert_eq!(Format::from_path(&json), Format::Json);
+}
+
+#[test]
+fn relative_paths_are_taken_from_the_current_directory() {
+    let mut app = App::new();
+    app.load_paths(vec![PathBuf::from("Cargo.toml")]);
+    let cwd = std::env::current_dir().unwrap();
+    assert_eq!(app.list.items[0].pathbuf, cwd.join("Cargo.toml"));
+}
+
+#[test]
+fn the_dry_run_document_lists_what_would_go() {
+    let dir = tempfile::tempdir().unwrap();
+    let (a, b) = (dir.path().join("a.log"), dir.path().join("b"));
+    fs::write(&a, "abc").unwrap();
+    fs::create_dir(&b).unwrap();
+    fs::write(b.join("c.log"), "de").unwrap();
+
+    let mut app = App::new();
+    app.load_paths(vec![a.clone(), b.clone()]);
+    app.mark_all();
+    let document = app.dry_run_document();
+    assert_eq!((document.files, document.dirs), (1, 1));
+    assert_eq!(document.total_size, 5);
+
+    let value: serde_json::Value = serde_json::from_str(&document.to_json().unwrap()).unwrap();
+    let entries = value["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().any(|e| e["type"] == "dir" && e["size"] == 2));
+    assert!(entries.iter().all(|e| e["modified"].is_string()));
+}
@@ -0,0 +1,16 @@
+use eradicate_core::history::History;
+
+#[test]
+fn browsing_goes_back_to_the_typed_pattern() {
+    let mut history = History::default();
+    history.push("**/target");
+    history.push("**/*.log");
+    history.push("**/target");
+
+    assert_eq!(history.older("draft"), Some("**/target"));
+    assert_eq!(history.older("**/target"), Some("**/*.log"));
+    assert_eq!(history.older("**/*.log"), None);
+    assert_eq!(history.newer(), Some("**/target"));
+    assert_eq!(history.newer(), Some("draft"));
+    assert_eq!(history.newer(), None);
+}
@@ -0,0 +1,37 @@
+use eradicate_core::log::{AuditRecord, Outcome};
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+#[test]
+fn audit_records_are_one_json_object_each() {
+    let time = SystemTime::UNIX_EPOCH + Duration::from_secs(86_400);
+    let trashed = AuditRecord::new(
+        time,
+        PathBuf::from("/tmp/a.log"),
+        Some(42),
+        Outcome::Trashed,
+    );
+    assert_eq!(
+        trashed.to_json().unwrap(),
+        r#"{"time":"1970-01-02T00:00:00Z","path":"/tmp/a.log","size":42,"outcome":"trashed"}"#
+    );
+
+    let mut failed = AuditRecord::new(time, PathBuf::from("/tmp/b\nc"), None, Outcome::Failed);
+    failed.error = Some(String::from("Permission denied"));
+    let json = failed.to_json().unwrap();
+    assert!(!json.contains('\n'));
+    assert!(json.contains(r#""size":null,"outcome":"failed","error":"Permission denied""#));
+}
+
+#[cfg(unix)]
+#[test]
+fn paths_that_are_not_utf8_are_still_logged() {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    let path = PathBuf::from(OsStr::from_bytes(b"/tmp/bad\xff.log"));
+    let record = AuditRecord::new(SystemTime::UNIX_EPOCH, path, Some(1), Outcome::Deleted);
+    let json = record.to_json().unwrap();
+    assert!(json.contains("\"path\":\"/tmp/bad\u{fffd}.log\""));
+}
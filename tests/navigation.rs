@@ -0,0 +1,67 @@
+use eradicate_core::StatefulList;
+
+#[test]
+fn selection_follows_the_kept_items() {
+    let mut list = StatefulList::with_items((0..100).collect::<Vec<_>>());
+    list.select_index(42);
+
+    list.retain(|n| n % 10 == 0);
+    assert_eq!(list.items, vec![0, 10, 20, 30, 40, 50, 60, 70, 80, 90]);
+    // 42 is gone, the next kept item is selected
+    assert_eq!(list.get_index(), Some(5));
+
+    list.retain(|n| *n != 30);
+    assert_eq!(list.items[list.get_index().unwrap()], 50);
+}
+
+#[test]
+fn removing_the_tail_clamps_the_selection() {
+    let mut list = StatefulList::with_items((0..10).collect::<Vec<_>>());
+    list.select_index(9);
+
+    list.retain(|n| *n < 3);
+    assert_eq!(list.get_index(), Some(2));
+
+    list.next();
+    assert_eq!(list.get_index(), Some(0));
+    list.previous();
+    assert_eq!(list.get_index(), Some(2));
+}
+
+#[test]
+fn navigation_stays_in_bounds_after_filtering_everything() {
+    let mut list = StatefulList::with_items((0..10).collect::<Vec<_>>());
+    list.select_index(5);
+
+    list.retain(|_| false);
+    assert_eq!(list.get_index(), None);
+
+    list.next();
+    assert_eq!(list.get_index(), None);
+    list.previous();
+    assert_eq!(list.get_index(), None);
+}
+
+#[test]
+fn empty_list_has_no_selection() {
+    let list = StatefulList::<u32>::with_items(vec![]);
+    assert_eq!(list.get_index(), None);
+}
+
+#[test]
+fn paging_stops_at_the_ends() {
+    let mut list = StatefulList::with_items((0..25).collect::<Vec<_>>());
+
+    list.page_down(10);
+    assert_eq!(list.get_index(), Some(10));
+    list.page_down(10);
+    list.page_down(10);
+    assert_eq!(list.get_index(), Some(24));
+    list.page_up(30);
+    assert_eq!(list.get_index(), Some(0));
+
+    list.last();
+    assert_eq!(list.get_index(), Some(24));
+    list.first();
+    assert_eq!(list.get_index(), Some(0));
+}
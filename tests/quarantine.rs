@@ -0,0 +1,120 @@
+use eradicate_core::quarantine::{self, Manifest, ManifestEntry};
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+#[test]
+fn stored_paths_keep_their_place_below_the_root() {
+    let root = Path::new("/home/me/project");
+    assert_eq!(
+        quarantine::stored_path(&root.join("target/debug"), root),
+        Path::new("target/debug")
+    );
+    assert_eq!(
+        quarantine::stored_path(Path::new("/tmp/cache"), root),
+        Path::new("_outside/tmp/cache")
+    );
+}
+
+#[test]
+fn quarantined_entries_are_restored_where_they_were() {
+    let root = tempfile::tempdir().unwrap();
+    let base = tempfile::tempdir().unwrap();
+    let build = root.path().join("build");
+    fs::create_dir(&build).unwrap();
+    fs::write(build.join("out.o"), "o").unwrap();
+
+    let time = SystemTime::UNIX_EPOCH + Duration::from_secs(86_400);
+    let dir = quarantine::create_batch(base.path(), time).unwrap();
+    assert_eq!(dir.file_name().unwrap(), "1970-01-02T00-00-00Z");
+    // a second batch in the same second gets its own directory
+    let other = quarantine::create_batch(base.path(), time).unwrap();
+    assert_ne!(dir, other);
+    fs::remove_dir(other).unwrap();
+
+    quarantine::stash(&build, &dir, root.path()).unwrap();
+    assert!(!build.exists());
+    assert!(dir.join("build/out.o").exists());
+    let manifest = Manifest {
+        created: String::from("1970-01-02T00:00:00Z"),
+        root: root.path().to_path_buf(),
+        entries: vec![ManifestEntry {
+            original: build.clone(),
+            stored: "build".into(),
+            size: 1,
+        }],
+    };
+    quarantine::write_manifest(&dir, &manifest).unwrap();
+
+    let batches = quarantine::list(base.path());
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].manifest, manifest);
+
+    assert_eq!(quarantine::restore(&batches[0]).unwrap(), 1);
+    assert_eq!(fs::read_to_string(build.join("out.o")).unwrap(), "o");
+    assert!(!dir.exists());
+    assert!(quarantine::list(base.path()).is_empty());
+}
+
+#[test]
+fn taken_paths_stay_in_the_quarantine() {
+    let root = tempfile::tempdir().unwrap();
+    let base = tempfile::tempdir().unwrap();
+    let file = root.path().join("a.log");
+    fs::write(&file, "old").unwrap();
+
+    let dir = quarantine::create_batch(base.path(), SystemTime::now()).unwrap();
+    quarantine::stash(&file, &dir, root.path()).unwrap();
+    let manifest = Manifest {
+        created: String::from("2024-01-01T00:00:00Z"),
+        root: root.path().to_path_buf(),
+        entries: vec![ManifestEntry {
+            original: file.clone(),
+            stored: "a.log".into(),
+            size: 3,
+        }],
+    };
+    quarantine::write_manifest(&dir, &manifest).unwrap();
+    fs::write(&file, "new").unwrap();
+
+    let batch = &quarantine::list(base.path())[0];
+    assert!(quarantine::restore(batch).is_err());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "new");
+    assert_eq!(quarantine::list(base.path())[0].manifest.entries.len(), 1);
+
+    quarantine::purge(batch).unwrap();
+    assert!(quarantine::list(base.path()).is_empty());
+}
+
+#[test]
+fn entries_never_moved_in_are_skipped_on_restore() {
+    let root = tempfile::tempdir().unwrap();
+    let base = tempfile::tempdir().unwrap();
+    let moved = root.path().join("a.log");
+    let left = root.path().join("b.log");
+    fs::write(&moved, "a").unwrap();
+    fs::write(&left, "b").unwrap();
+
+    // the manifest lists the whole deletion, cut short after the first entry
+    let dir = quarantine::create_batch(base.path(), SystemTime::now()).unwrap();
+    let entry = |path: &Path, stored: &str| ManifestEntry {
+        original: path.to_path_buf(),
+        stored: stored.into(),
+        size: 1,
+    };
+    let manifest = Manifest {
+        created: String::from("2024-01-01T00:00:00Z"),
+        root: root.path().to_path_buf(),
+        entries: vec![entry(&moved, "a.log"), entry(&left, "b.log")],
+    };
+    quarantine::write_manifest(&dir, &manifest).unwrap();
+    quarantine::stash(&moved, &dir, root.path()).unwrap();
+
+    let batch = &quarantine::list(base.path())[0];
+    assert_eq!(quarantine::restore(batch).unwrap(), 1);
+    assert_eq!(fs::read_to_string(&moved).unwrap(), "a");
+    assert_eq!(fs::read_to_string(&left).unwrap(), "b");
+    assert!(!dir.exists());
+}
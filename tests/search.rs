@@ -0,0 +1,424 @@
+use eradicate_core::{search::MatchEngine, App, MarkView};
+use std::{
+    fs,
+    time::{Duration, SystemTime},
+};
+
+#[test]
+fn absolute_pattern_ignores_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.log"), "a").unwrap();
+    fs::write(dir.path().join("b.log"), "b").unwrap();
+    fs::write(dir.path().join("c.txt"), "c").unwrap();
+
+    let other = tempfile::tempdir().unwrap();
+    let mut app = App::new();
+    app.root = other.path().to_path_buf();
+    app.pattern.content = format!("{}/*.log", dir.path().display());
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+
+    let mut paths: Vec<_> = app.list.items.iter().map(|e| e.pathbuf.clone()).collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![dir.path().join("a.log"), dir.path().join("b.log")]
+    );
+
+    assert!(app.is_absolute_pattern());
+    let entry = &app.list.items[0];
+    assert_eq!(app.display_path(entry), entry.pathbuf.as_path());
+}
+
+#[test]
+fn relative_pattern_is_resolved_against_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("logs")).unwrap();
+    fs::write(dir.path().join("logs/a.log"), "a").unwrap();
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.pattern.content = String::from("logs/*.log");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+
+    assert_eq!(app.list.items.len(), 1);
+    let entry = &app.list.items[0];
+    assert_eq!(app.display_path(entry), std::path::Path::new("logs/a.log"));
+}
+
+#[test]
+fn exclude_patterns_leave_matches_out() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("important")).unwrap();
+    fs::write(dir.path().join("a.log"), "a").unwrap();
+    fs::write(dir.path().join("important/b.log"), "b").unwrap();
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.pattern.content = String::from("**/*.log !important/*.log");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+
+    let paths: Vec<_> = app.list.items.iter().map(|e| e.pathbuf.clone()).collect();
+    assert_eq!(paths, vec![dir.path().join("a.log")]);
+}
+
+#[test]
+fn several_patterns_are_merged_without_duplicates() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("target")).unwrap();
+    fs::create_dir(dir.path().join("dist")).unwrap();
+    fs::write(dir.path().join("c.txt"), "c").unwrap();
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.pattern.content = String::from("target, dist ta*");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+
+    let mut paths: Vec<_> = app.list.items.iter().map(|e| e.pathbuf.clone()).collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![dir.path().join("dist"), dir.path().join("target")]
+    );
+}
+
+#[test]
+fn ignored_entries_are_skipped_when_respecting_ignore_files() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("keep")).unwrap();
+    fs::write(dir.path().join("a.log"), "a").unwrap();
+    fs::write(dir.path().join("keep/b.log"), "b").unwrap();
+    fs::write(dir.path().join(".eradicateignore"), "keep/\n").unwrap();
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.config.respect_ignore = true;
+    app.pattern.content = String::from("**/*.log");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+
+    let paths: Vec<_> = app.list.items.iter().map(|e| e.pathbuf.clone()).collect();
+    assert_eq!(paths, vec![dir.path().join("a.log")]);
+}
+
+#[test]
+fn age_criteria_keep_old_or_recent_matches() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("old.log"), "o").unwrap();
+    fs::write(dir.path().join("new.log"), "n").unwrap();
+    let long_ago = SystemTime::now() - Duration::from_secs(60 * 86_400);
+    fs::File::options()
+        .write(true)
+        .open(dir.path().join("old.log"))
+        .unwrap()
+        .set_modified(long_ago)
+        .unwrap();
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    for (pattern, expected) in [
+        ("*.log +mtime:30d", "old.log"),
+        ("*.log -mtime:1d", "new.log"),
+    ] {
+        app.pattern.content = String::from(pattern);
+        app.set_pattern().unwrap();
+        app.wait_for_search();
+
+        let names: Vec<_> = app
+            .list
+            .items
+            .iter()
+            .map(|e| e.pathbuf.file_name().unwrap().to_owned())
+            .collect();
+        assert_eq!(names, vec![expected]);
+    }
+    assert_eq!(app.criteria.len(), 1);
+
+    app.pattern.content = String::from("*.log +mtime:soon");
+    app.set_pattern().unwrap();
+    assert_eq!(app.pattern_error.map(|err| err.span), Some(6..17));
+}
+
+#[test]
+fn size_criteria_keep_big_or_small_matches() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("big.bin"), vec![0; 4096]).unwrap();
+    fs::write(dir.path().join("small.bin"), "s").unwrap();
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    for (pattern, expected) in [("*.bin size>1K", "big.bin"), ("*.bin size<1K", "small.bin")] {
+        app.pattern.content = String::from(pattern);
+        app.set_pattern().unwrap();
+        app.wait_for_search();
+
+        let names: Vec<_> = app
+            .list
+            .items
+            .iter()
+            .map(|e| e.pathbuf.file_name().unwrap().to_owned())
+            .collect();
+        assert_eq!(names, vec![expected]);
+    }
+    assert_eq!(app.criteria[0].to_string(), "smaller than 1.0K");
+}
+
+#[test]
+fn builtin_presets_skip_nested_copies() {
+    let dir = tempfile::tempdir().unwrap();
+    let outer = dir.path().join("app/node_modules");
+    fs::create_dir_all(outer.join("dep/node_modules")).unwrap();
+
+    let preset = eradicate_core::presets::builtin()
+        .into_iter()
+        .find(|preset| preset.name == "node_modules")
+        .unwrap();
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.pattern.content = preset.pattern;
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+
+    let paths: Vec<_> = app.list.items.iter().map(|e| e.pathbuf.clone()).collect();
+    assert_eq!(paths, vec![outer]);
+}
+
+#[test]
+fn patterns_share_one_walk_in_file_name_order() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("sub/deep")).unwrap();
+    for name in ["a.log", "b.tmp", "c.txt", "sub/d.log", "sub/deep/e.log"] {
+        fs::write(dir.path().join(name), "x").unwrap();
+    }
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.pattern.content = String::from("sub/*.log *.{log,tmp}");
+    app.config.search_threads = 1;
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+
+    let expected = vec![
+        dir.path().join("a.log"),
+        dir.path().join("b.tmp"),
+        dir.path().join("sub/d.log"),
+    ];
+    let paths: Vec<_> = app.list.items.iter().map(|e| e.pathbuf.clone()).collect();
+    assert_eq!(paths, expected);
+
+    // in any order with more threads
+    app.config.search_threads = 4;
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+    let mut paths: Vec<_> = app.list.items.iter().map(|e| e.pathbuf.clone()).collect();
+    paths.sort();
+    assert_eq!(paths, expected);
+}
+
+#[test]
+fn matches_past_the_cap_wait_to_be_loaded() {
+    let dir = tempfile::tempdir().unwrap();
+    for i in 0..5 {
+        fs::write(dir.path().join(format!("{}.log", i)), "x").unwrap();
+    }
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.config.max_results = 2;
+    app.pattern.content = String::from("*.log");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+    assert_eq!(app.list.items.len(), 2);
+    assert_eq!(app.overflow.len(), 3);
+
+    app.load_more_results();
+    assert_eq!(app.list.items.len(), 4);
+    app.load_more_results();
+    assert_eq!(app.list.items.len(), 5);
+    assert!(app.overflow.is_empty());
+    assert_eq!(eradicate_core::humanize::count(113_482), "113,482");
+}
+
+#[test]
+fn invalid_patterns_point_at_the_mistake() {
+    let mut app = App::new();
+    for (pattern, span) in [
+        ("*.log src/[ab", 10..13),
+        ("*.{log,tmp", 2..10),
+        ("*.log !a}", 8..9),
+        ("*.log,[z-a].tmp", 7..10),
+    ] {
+        app.pattern.content = String::from(pattern);
+        app.set_pattern().unwrap();
+        let err = app.pattern_error.clone().expect(pattern);
+        assert_eq!(err.span, span, "{}", pattern);
+        assert!(app.search.is_none());
+    }
+
+    // editing clears the error, a valid pattern runs
+    app.pop_ch();
+    assert!(app.pattern_error.is_none());
+    app.pattern.content = String::from("*.log");
+    app.set_pattern().unwrap();
+    assert!(app.pattern_error.is_none());
+}
+
+#[test]
+fn invalid_regexes_point_at_the_mistake() {
+    let mut app = App::new();
+    app.engine = MatchEngine::Regex;
+    app.pattern.content = String::from("-mtime:1d log(s");
+    app.set_pattern().unwrap();
+
+    let err = app.pattern_error.unwrap();
+    assert_eq!(err.message, "unclosed group");
+    assert_eq!(err.span, 13..14);
+}
+
+#[test]
+fn the_matched_parts_of_each_path_are_found() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("logs/old")).unwrap();
+    fs::write(dir.path().join("logs/old/a.log"), "a").unwrap();
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.pattern.content = String::from("logs/**/*.log");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+
+    // "logs/old/a.log", the components either side of `**`
+    let entry = app.list.items[0].clone();
+    assert_eq!(app.match_spans(&entry), vec![0..4, 9..14]);
+
+    app.engine = MatchEngine::Regex;
+    app.pattern.content = String::from(r"o\w");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+    let entry = app
+        .list
+        .items
+        .iter()
+        .find(|entry| entry.pathbuf.ends_with("a.log"))
+        .unwrap()
+        .clone();
+    assert_eq!(app.match_spans(&entry), vec![1..3, 5..7, 12..14]);
+
+    // nothing to show for pasted in paths
+    app.load_paths(vec![entry.pathbuf.clone()]);
+    assert!(app.match_spans(&app.list.items[0]).is_empty());
+}
+
+#[test]
+fn regexes_and_text_are_searched_as_typed() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a  b.log"), "a").unwrap();
+    fs::write(dir.path().join("notes.txt"), "!keep  this").unwrap();
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.engine = MatchEngine::Regex;
+    app.pattern.content = String::from(r"a  b\.log size<1K");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+    assert_eq!(app.list.items.len(), 1);
+    assert!(app.list.items[0].pathbuf.ends_with("a  b.log"));
+
+    // no `!` exclude outside of globs
+    app.engine = MatchEngine::Content;
+    app.pattern.content = String::from("!keep  this");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+    assert_eq!(app.list.items.len(), 1);
+    assert!(app.list.items[0].pathbuf.ends_with("notes.txt"));
+}
+
+#[test]
+fn resetting_the_search_options_drops_excludes_and_criteria() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.log"), "a").unwrap();
+    fs::write(dir.path().join("b.log"), "b").unwrap();
+    fs::write(dir.path().join("big.log"), vec![b'x'; 2048]).unwrap();
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    let case_sensitive = app.is_case_sensitive();
+    let respect_ignore = app.respect_ignore();
+    app.pattern.content = String::from("*.log !b.log size<1K");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+    let names = |app: &App| -> Vec<String> {
+        let mut names: Vec<_> = app
+            .list
+            .items
+            .iter()
+            .map(|e| {
+                e.pathbuf
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names(&app), vec!["a.log"]);
+
+    app.toggle_case_sensitive();
+    app.toggle_respect_ignore();
+    app.cycle_mark_view();
+    app.set_filter("zzz");
+    // reset while the search still runs, with the exclude shown
+    app.set_pattern().unwrap();
+    app.cycle_engine();
+
+    app.reset_search_options().unwrap();
+    assert_eq!(app.pattern.content, "*.log");
+    assert_eq!(app.is_case_sensitive(), case_sensitive);
+    assert_eq!(app.respect_ignore(), respect_ignore);
+    assert_eq!(app.engine, MatchEngine::Glob);
+    assert_eq!(app.mark_view, MarkView::All);
+    assert!(app.filter.is_empty());
+    assert!(app.criteria.is_empty());
+
+    // what's listed matches the pattern shown, nothing else
+    app.wait_for_search();
+    assert_eq!(names(&app), vec!["a.log", "b.log", "big.log"]);
+}
+
+#[test]
+fn skipping_ignored_entries_is_toggled_for_this_run_only() {
+    let mut app = App::new();
+    let respect_ignore = app.config.respect_ignore;
+
+    app.toggle_respect_ignore();
+    assert_eq!(app.respect_ignore(), !respect_ignore);
+    assert_eq!(app.config.respect_ignore, respect_ignore);
+
+    app.toggle_respect_ignore();
+    assert_eq!(app.respect_ignore(), respect_ignore);
+}
+
+#[test]
+fn watching_is_toggled_for_this_run_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    let watch = app.config.watch;
+
+    app.toggle_watch();
+    assert_eq!(app.watch_root(), !watch);
+    assert_eq!(app.watcher.is_some(), !watch);
+    assert_eq!(app.config.watch, watch);
+
+    // a root that can't be watched turns watching off, still only for this run
+    app.root = dir.path().join("missing");
+    app.update_watcher();
+    assert!(!app.watch_root());
+    assert!(app.watcher.is_none());
+    assert_eq!(app.config.watch, watch);
+}
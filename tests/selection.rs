@@ -0,0 +1,233 @@
+use eradicate_core::{App, Area, MarkView, PathEntry, StatefulList};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+fn app_with(paths: &[&str]) -> App {
+    let mut app = App::new();
+    let entries = paths
+        .iter()
+        .map(|p| PathEntry::new(PathBuf::from(p)))
+        .collect();
+    app.list = StatefulList::with_items(entries);
+    app
+}
+
+#[test]
+fn select_by_path_selects_the_matching_entry() {
+    let mut app = app_with(&["a.log", "b.log", "c.log"]);
+
+    assert!(app.select_by_path(Path::new("c.log")));
+    assert_eq!(app.list.get_index(), Some(2));
+    assert_eq!(app.selected_path(), Some(Path::new("c.log")));
+
+    assert!(app.select_by_path(Path::new("a.log")));
+    assert_eq!(app.selected_path(), Some(Path::new("a.log")));
+}
+
+#[test]
+fn select_by_path_keeps_the_selection_when_missing() {
+    let mut app = app_with(&["a.log", "b.log"]);
+    app.list.select_index(1);
+
+    assert!(!app.select_by_path(Path::new("missing.log")));
+    assert_eq!(app.selected_path(), Some(Path::new("b.log")));
+}
+
+#[test]
+fn selected_path_is_none_without_entries() {
+    let mut app = App::new();
+    assert_eq!(app.selected_path(), None);
+    assert!(!app.select_by_path(Path::new("a.log")));
+}
+
+#[test]
+fn clicks_map_screen_rows_to_entries() {
+    let mut app = app_with(&["a.log", "b.log", "c.log", "d.log"]);
+    app.list_area = Area::new(10, 5, 40, 4);
+    app.rows_per_entry = 2;
+    app.list_offset = 1;
+
+    assert_eq!(app.entry_at(10, 5), Some((1, true)));
+    assert_eq!(app.entry_at(20, 8), Some((2, false)));
+    assert_eq!(app.entry_at(9, 5), None);
+    assert_eq!(app.entry_at(10, 9), None);
+
+    app.list_offset = 3;
+    assert_eq!(app.entry_at(10, 7), None);
+}
+
+#[test]
+fn double_click_toggles_the_entry() {
+    let mut app = app_with(&["a.log", "b.log"]);
+
+    app.click_entry(1, false);
+    assert_eq!(app.list.get_index(), Some(1));
+    assert!(app.list.items[1].is_delete());
+
+    app.click_entry(1, false);
+    assert!(!app.list.items[1].is_delete());
+
+    // clicking the marker toggles right away
+    app.click_entry(0, true);
+    assert!(!app.list.items[0].is_delete());
+}
+
+#[test]
+fn marks_survive_refining_the_pattern() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["a.log", "b.log", "c.txt"] {
+        std::fs::write(dir.path().join(name), name).unwrap();
+    }
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.config.search_threads = 1;
+
+    app.pattern.content = String::from("*.log");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+    assert!(app.select_by_path(&dir.path().join("b.log")));
+    app.toggle_delete();
+
+    app.pattern.content = String::from("*.log,*.txt");
+    app.set_pattern().unwrap();
+    app.wait_for_search();
+    let marks: Vec<(String, bool)> = app
+        .list
+        .items
+        .iter()
+        .map(|e| (app.display_path(e).display().to_string(), e.is_delete()))
+        .collect();
+    assert_eq!(
+        marks,
+        [
+            (String::from("a.log"), true),
+            (String::from("b.log"), false),
+            (String::from("c.txt"), true),
+        ]
+    );
+}
+
+#[test]
+fn yanking_copies_the_selected_or_marked_paths() {
+    let mut app = app_with(&["/tmp/a.log", "/tmp/b.log", "/tmp/c.log"]);
+    app.list.select_index(1);
+    app.yank_selected();
+    assert_eq!(app.clipboard.as_deref(), Some("/tmp/b.log"));
+
+    app.toggle_delete();
+    app.yank_marked();
+    assert_eq!(app.clipboard.as_deref(), Some("/tmp/a.log\n/tmp/c.log"));
+
+    app.clipboard = None;
+    app.unmark_all();
+    app.yank_marked();
+    assert!(app.clipboard.is_none());
+}
+
+#[test]
+fn marking_changes_undo_and_redo_in_order() {
+    let mut app = app_with(&["a.log", "b.log", "c.log"]);
+    let marks = |app: &App| -> Vec<bool> { app.list.items.iter().map(|e| e.is_delete()).collect() };
+    app.unmark_all();
+    app.list.select_index(1);
+    app.toggle_delete();
+    app.invert_marks();
+    assert_eq!(marks(&app), [true, false, true]);
+
+    app.undo();
+    assert_eq!(marks(&app), [false, true, false]);
+    app.undo();
+    assert_eq!(marks(&app), [false, false, false]);
+    app.redo_marks();
+    assert_eq!(marks(&app), [false, true, false]);
+
+    // a new change drops what was left to redo
+    app.mark_all();
+    app.redo_marks();
+    assert_eq!(app.status.as_deref(), Some("nothing to redo"));
+    app.undo();
+    assert_eq!(marks(&app), [false, true, false]);
+    assert_eq!(app.mark_decisions.get(Path::new("a.log")), Some(&false));
+}
+
+#[test]
+fn the_mark_view_shows_marked_then_unmarked_entries() {
+    let mut app = app_with(&["a.log", "b.log", "c.txt"]);
+    app.unmark_all();
+    app.list.select_index(1);
+    app.toggle_delete();
+    let listed =
+        |app: &App| -> Vec<PathBuf> { app.list.items.iter().map(|e| e.pathbuf.clone()).collect() };
+
+    app.cycle_mark_view();
+    assert_eq!(app.mark_view, MarkView::Marked);
+    assert_eq!(listed(&app), [PathBuf::from("b.log")]);
+
+    app.cycle_mark_view();
+    assert_eq!(
+        listed(&app),
+        [PathBuf::from("a.log"), PathBuf::from("c.txt")]
+    );
+    // along with the filter
+    app.set_filter("*.log");
+    assert_eq!(listed(&app), [PathBuf::from("a.log")]);
+
+    app.set_filter("");
+    app.cycle_mark_view();
+    assert_eq!(app.mark_view, MarkView::All);
+    assert_eq!(app.list.items.len(), 3);
+    assert_eq!(app.selected_path(), Some(Path::new("a.log")));
+}
+
+#[test]
+fn the_histogram_sizes_directories_by_their_contents() {
+    let dir = tempfile::tempdir().unwrap();
+    let big = dir.path().join("big");
+    fs::create_dir(&big).unwrap();
+    fs::create_dir(dir.path().join("unsized")).unwrap();
+    fs::write(dir.path().join("a.log"), "a").unwrap();
+
+    let mut app = App::new();
+    app.list = StatefulList::with_items(
+        ["big", "unsized", "a.log"]
+            .iter()
+            .map(|name| PathEntry::new(dir.path().join(name)))
+            .collect(),
+    );
+    app.dir_sizes.insert(big, 2 * 1024 * 1024 * 1024);
+
+    let counts: Vec<u64> = app.size_histogram().into_iter().map(|(_, n)| n).collect();
+    assert_eq!(counts, vec![1, 0, 0, 0, 0, 1]);
+}
+
+#[test]
+fn the_inspector_reports_the_selected_entry() {
+    let dir = tempfile::tempdir().unwrap();
+    let big = dir.path().join("big");
+    fs::create_dir(&big).unwrap();
+    fs::write(dir.path().join("a.log"), "abc").unwrap();
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    assert!(app.inspect_selected().is_none());
+    app.list = StatefulList::with_items(vec![
+        PathEntry::new(big.clone()),
+        PathEntry::new(dir.path().join("a.log")),
+    ]);
+
+    let details = app.inspect_selected().unwrap();
+    assert_eq!(details.path, big);
+    assert_eq!(details.kind, "Dir");
+    assert_eq!(details.size, None);
+    assert!(!details.outside_root);
+
+    app.dir_sizes.insert(big, 5 * 1024 * 1024);
+    assert_eq!(app.inspect_selected().unwrap().size, Some(5 * 1024 * 1024));
+
+    app.list.next();
+    let details = app.inspect_selected().unwrap();
+    assert_eq!(details.kind, "File");
+    assert_eq!(details.size, Some(3));
+}
@@ -0,0 +1,58 @@
+#![cfg(unix)]
+
+use eradicate_core::{dir_stats, search::MatchEngine, App, PathEntry};
+use std::{fs, path::Path, process::Command, thread, time::Duration};
+
+fn mkfifo(path: &Path) -> bool {
+    Command::new("mkfifo")
+        .arg(path)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[test]
+fn fifo_is_sized_without_being_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let fifo = dir.path().join("pipe");
+    if !mkfifo(&fifo) {
+        eprintln!("mkfifo is unavailable, skipping");
+        return;
+    }
+    fs::write(dir.path().join("data"), "12345").unwrap();
+
+    let entry = PathEntry::new(fifo);
+    assert_eq!(entry.special, Some("FIFO"));
+    assert_eq!(entry.size, 0);
+    assert!(!entry.is_dir);
+
+    assert_eq!(dir_stats(dir.path()), (2, 5));
+}
+
+#[test]
+fn content_search_skips_fifos() {
+    let dir = tempfile::tempdir().unwrap();
+    if !mkfifo(&dir.path().join("pipe")) {
+        eprintln!("mkfifo is unavailable, skipping");
+        return;
+    }
+    fs::write(dir.path().join("notes.txt"), "needle").unwrap();
+
+    let mut app = App::new();
+    app.root = dir.path().to_path_buf();
+    app.engine = MatchEngine::Content;
+    app.pattern.content = String::from("needle");
+    app.set_pattern().unwrap();
+
+    for _ in 0..500 {
+        app.poll_search();
+        if !app.is_searching() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert!(!app.is_searching(), "the search got stuck");
+    assert_eq!(app.list.items.len(), 1);
+    assert_eq!(app.list.items[0].pathbuf, dir.path().join("notes.txt"));
+}
@@ -0,0 +1,80 @@
+use eradicate_core::{App, PathEntry, StatefulList};
+use std::path::PathBuf;
+
+fn tree_with(paths: &[&str]) -> App {
+    let mut app = App::new();
+    let entries = paths
+        .iter()
+        .map(|p| PathEntry::new(PathBuf::from(p)))
+        .collect();
+    app.list = StatefulList::with_items(entries);
+    app.toggle_tree_view();
+    app
+}
+
+fn listed(app: &App) -> Vec<String> {
+    app.list
+        .items
+        .iter()
+        .map(|e| e.pathbuf.display().to_string())
+        .collect()
+}
+
+#[test]
+fn entries_follow_a_heading_for_their_directory() {
+    let app = tree_with(&["b/y.log", "top.log", "a/x.log", "b/c/z.log"]);
+
+    assert_eq!(
+        listed(&app),
+        vec![
+            "a",
+            "a/x.log",
+            "b",
+            "b/c",
+            "b/c/z.log",
+            "b/y.log",
+            "top.log"
+        ]
+    );
+    assert!(app.list.items[0].group);
+    assert!(!app.list.items[1].group);
+    assert_eq!(app.tree_depth(&app.list.items[4]), 2);
+}
+
+#[test]
+fn toggling_a_heading_marks_everything_inside() {
+    let mut app = tree_with(&["a/x.log", "a/b/y.log", "c.log"]);
+    app.unmark_all();
+
+    app.list.select_index(0);
+    app.toggle_delete();
+    assert_eq!(app.group_marks(0), (2, 2));
+    assert!(!app.list.items[0].is_delete());
+    assert!(!app.list.items.last().unwrap().is_delete());
+
+    app.toggle_delete();
+    assert_eq!(app.group_marks(0), (0, 2));
+}
+
+#[test]
+fn collapsed_entries_come_back_before_deleting() {
+    let mut app = tree_with(&["a/x.log", "a/y.log", "c.log"]);
+
+    app.list.select_index(1);
+    app.collapse_selected();
+    assert_eq!(listed(&app), vec!["a", "c.log"]);
+    assert_eq!(app.list.get_index(), Some(0));
+    assert_eq!(app.collapsed_count(&PathBuf::from("a")), Some(2));
+    assert_eq!(app.group_marks(0), (2, 2));
+
+    app.request_delete(false);
+    assert_eq!(listed(&app), vec!["a", "a/x.log", "a/y.log", "c.log"]);
+}
+
+#[test]
+fn leaving_the_tree_view_drops_the_headings() {
+    let mut app = tree_with(&["a/x.log", "c.log"]);
+
+    app.toggle_tree_view();
+    assert_eq!(listed(&app), vec!["a/x.log", "c.log"]);
+}